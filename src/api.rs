@@ -1,4 +1,4 @@
-use crate::monitoring::{CheckResult, TargetStatus};
+use crate::monitoring::{CheckResult, CheckStatus, TargetStatus};
 use actix_web::{get, web, App, HttpResponse, HttpServer, Responder};
 use log::{error, info}; // Added log macros
 use prometheus::process_collector::ProcessCollector;
@@ -31,6 +31,30 @@ const TYPE_MONITOR_CERT_DAYS_REMAINING: &str = "# TYPE monitor_cert_days_remaini
 const HELP_MONITOR_CERT_IS_VALID: &str =
     "# HELP monitor_cert_is_valid Is the certificate still valid? (1 = Yes, 0 = No)";
 const TYPE_MONITOR_CERT_IS_VALID: &str = "# TYPE monitor_cert_is_valid gauge";
+
+const HELP_MONITOR_WEBSOCKET_HANDSHAKE_TIME: &str =
+    "# HELP monitor_websocket_handshake_time Time in milliseconds until the WebSocket upgrade completed.";
+const TYPE_MONITOR_WEBSOCKET_HANDSHAKE_TIME: &str = "# TYPE monitor_websocket_handshake_time gauge";
+
+const HELP_MONITOR_WEBSOCKET_MESSAGE_RTT: &str =
+    "# HELP monitor_websocket_message_rtt Round trip time in milliseconds of the WebSocket test message.";
+const TYPE_MONITOR_WEBSOCKET_MESSAGE_RTT: &str = "# TYPE monitor_websocket_message_rtt gauge";
+
+const HELP_MONITOR_HTTP_PHASE_TIME: &str =
+    "# HELP monitor_http_phase_time Time in milliseconds spent in each phase (dns, connect, tls, ttfb, download) of the last HTTP request.";
+const TYPE_MONITOR_HTTP_PHASE_TIME: &str = "# TYPE monitor_http_phase_time gauge";
+
+const HELP_MONITOR_ADDRESS_STATUS: &str =
+    "# HELP monitor_address_status Is the resolved address up? (1 = Yes, 0 = No), for HTTP checks with check_each_address.";
+const TYPE_MONITOR_ADDRESS_STATUS: &str = "# TYPE monitor_address_status gauge";
+
+const HELP_MONITOR_ADDRESS_RESPONSE_TIME: &str =
+    "# HELP monitor_address_response_time Response time in milliseconds of the resolved address, for HTTP checks with check_each_address.";
+const TYPE_MONITOR_ADDRESS_RESPONSE_TIME: &str = "# TYPE monitor_address_response_time gauge";
+
+const HELP_MONITOR_OAUTH2_TOKEN_STATUS: &str =
+    "# HELP monitor_oauth2_token_status Was an OAuth2 token obtained for the last check? (1 = Yes, 0 = No), for HTTP checks with OAuth2.";
+const TYPE_MONITOR_OAUTH2_TOKEN_STATUS: &str = "# TYPE monitor_oauth2_token_status gauge";
 // --- End Prometheus Metric Definitions ---
 
 // Helper to escape label values for Prometheus
@@ -149,6 +173,11 @@ async fn badge_handler(
             let response_time = match &status.last_result {
                 Some(CheckResult::Http(http_details)) => Some(http_details.response_time_ms),
                 Some(CheckResult::Tcp(tcp_details)) => Some(tcp_details.response_time_ms),
+                Some(CheckResult::Smtp(mail_details))
+                | Some(CheckResult::Imap(mail_details))
+                | Some(CheckResult::Pop3(mail_details)) => Some(mail_details.response_time_ms),
+                Some(CheckResult::WebSocket(ws_details)) => Some(ws_details.response_time_ms),
+                Some(CheckResult::Tls(tls_details)) => Some(tls_details.response_time_ms),
                 _ => None,
             };
 
@@ -271,22 +300,34 @@ async fn metrics_handler(data: web::Data<Arc<Mutex<Vec<TargetStatus>>>>) -> impl
 
     // General Metrics (apply to all types)
     custom_metrics_output.push_str(HELP_MONITOR_STATUS);
-    custom_metrics_output.push_str("\n");
+    custom_metrics_output.push('\n');
     custom_metrics_output.push_str(TYPE_MONITOR_STATUS);
-    custom_metrics_output.push_str("\n");
+    custom_metrics_output.push('\n');
 
     let mut status_metrics_buffer = String::new();
 
     custom_metrics_output.push_str(HELP_MONITOR_CONSECUTIVE_FAILURES);
-    custom_metrics_output.push_str("\n");
+    custom_metrics_output.push('\n');
     custom_metrics_output.push_str(TYPE_MONITOR_CONSECUTIVE_FAILURES);
-    custom_metrics_output.push_str("\n");
+    custom_metrics_output.push('\n');
     let mut consecutive_failures_buffer = String::new();
 
     // HTTP Specific Metrics
     let mut http_metrics_buffer = String::new();
     let mut has_http_metrics = false; // To know if we need to print HTTP specific HELP/TYPE
 
+    // WebSocket Specific Metrics
+    let mut websocket_metrics_buffer = String::new();
+    let mut websocket_rtt_buffer = String::new();
+
+    // HTTP phase timings
+    let mut http_phase_buffer = String::new();
+
+    // Per-address HTTP results
+    let mut address_status_buffer = String::new();
+    let mut address_response_time_buffer = String::new();
+    let mut oauth2_token_buffer = String::new();
+
     // Iterate once and build up metric strings for custom metrics
     for status in statuses.iter() {
         let monitor_name = escape_label_value(&status.target_alias);
@@ -304,6 +345,11 @@ async fn metrics_handler(data: web::Data<Arc<Mutex<Vec<TargetStatus>>>>) -> impl
             Some(CheckResult::MySQL(_)) => "mysql",
             Some(CheckResult::MongoDB(_)) => "mongodb",
             Some(CheckResult::Elasticsearch(_)) => "elasticsearch",
+            Some(CheckResult::Smtp(_)) => "smtp",
+            Some(CheckResult::Imap(_)) => "imap",
+            Some(CheckResult::Pop3(_)) => "pop3",
+            Some(CheckResult::WebSocket(_)) => "websocket",
+            Some(CheckResult::Tls(_)) => "tls",
             None => "unknown",
         };
 
@@ -327,8 +373,17 @@ async fn metrics_handler(data: web::Data<Arc<Mutex<Vec<TargetStatus>>>>) -> impl
             labels, status.consecutive_failures
         );
 
-        // HTTP specific metrics
-        if let Some(CheckResult::Http(http_details)) = &status.last_result {
+        // HTTP, mail, WebSocket and TLS (certificate-bearing) metrics
+        let tls_response_time = match &status.last_result {
+            Some(CheckResult::Http(http_details)) => Some(http_details.response_time_ms),
+            Some(CheckResult::Smtp(mail_details))
+            | Some(CheckResult::Imap(mail_details))
+            | Some(CheckResult::Pop3(mail_details)) => Some(mail_details.response_time_ms),
+            Some(CheckResult::WebSocket(ws_details)) => Some(ws_details.response_time_ms),
+            Some(CheckResult::Tls(tls_details)) => Some(tls_details.response_time_ms),
+            _ => None,
+        };
+        if let Some(response_time_ms) = tls_response_time {
             if !has_http_metrics {
                 has_http_metrics = true; // Mark that we have HTTP metrics to print HELP/TYPE lines later
             }
@@ -337,7 +392,7 @@ async fn metrics_handler(data: web::Data<Arc<Mutex<Vec<TargetStatus>>>>) -> impl
             let _ = writeln!(
                 http_metrics_buffer,
                 "monitor_response_time{{{}}} {}",
-                labels, http_details.response_time_ms
+                labels, response_time_ms
             );
 
             // monitor_cert_days_remaining
@@ -361,6 +416,75 @@ async fn metrics_handler(data: web::Data<Arc<Mutex<Vec<TargetStatus>>>>) -> impl
                 labels, cert_valid_value
             );
         }
+        if let Some(CheckResult::WebSocket(ws_details)) = &status.last_result {
+            let _ = writeln!(
+                websocket_metrics_buffer,
+                "monitor_websocket_handshake_time{{{}}} {}",
+                labels, ws_details.handshake_time_ms
+            );
+            if let Some(rtt) = ws_details.message_rtt_ms {
+                let _ = writeln!(
+                    websocket_rtt_buffer,
+                    "monitor_websocket_message_rtt{{{}}} {}",
+                    labels, rtt
+                );
+            }
+        }
+        let http_timings = match &status.last_result {
+            Some(CheckResult::Http(http_details)) => http_details.timings,
+            _ => None,
+        };
+        if let Some(timings) = http_timings {
+            let phases = [
+                ("dns", Some(timings.dns_ms)),
+                ("connect", Some(timings.connect_ms)),
+                ("tls", timings.tls_ms),
+                ("ttfb", Some(timings.ttfb_ms)),
+                ("download", Some(timings.download_ms)),
+            ];
+            for (phase, value) in phases {
+                if let Some(value) = value {
+                    let _ = writeln!(
+                        http_phase_buffer,
+                        "monitor_http_phase_time{{{},phase=\"{}\"}} {}",
+                        labels, phase, value
+                    );
+                }
+            }
+        }
+        if let Some(CheckResult::Http(http_details)) = &status.last_result {
+            // Tells a token endpoint failure apart from a failing target
+            if let Some(token_ok) = http_details.oauth2_token_ok {
+                let _ = writeln!(
+                    oauth2_token_buffer,
+                    "monitor_oauth2_token_status{{{}}} {}",
+                    labels,
+                    if token_ok { 1 } else { 0 }
+                );
+            }
+            for address in &http_details.addresses {
+                let address_labels = format!(
+                    "{},address=\"{}\"",
+                    labels,
+                    escape_label_value(&address.address)
+                );
+                let up = if matches!(address.status, CheckStatus::Healthy) {
+                    1
+                } else {
+                    0
+                };
+                let _ = writeln!(
+                    address_status_buffer,
+                    "monitor_address_status{{{}}} {}",
+                    address_labels, up
+                );
+                let _ = writeln!(
+                    address_response_time_buffer,
+                    "monitor_address_response_time{{{}}} {}",
+                    address_labels, address.response_time_ms
+                );
+            }
+        }
         if let Some(CheckResult::Tcp(tcp_details)) = &status.last_result {
             // monitor_response_time
             let _ = writeln!(
@@ -368,7 +492,6 @@ async fn metrics_handler(data: web::Data<Arc<Mutex<Vec<TargetStatus>>>>) -> impl
                 "monitor_response_time{{{}}} {}",
                 labels, tcp_details.response_time_ms
             );
-
         }
     }
 
@@ -378,23 +501,69 @@ async fn metrics_handler(data: web::Data<Arc<Mutex<Vec<TargetStatus>>>>) -> impl
 
     if has_http_metrics {
         custom_metrics_output.push_str(HELP_MONITOR_RESPONSE_TIME);
-        custom_metrics_output.push_str("\n");
+        custom_metrics_output.push('\n');
         custom_metrics_output.push_str(TYPE_MONITOR_RESPONSE_TIME);
-        custom_metrics_output.push_str("\n");
+        custom_metrics_output.push('\n');
 
         custom_metrics_output.push_str(HELP_MONITOR_CERT_DAYS_REMAINING);
-        custom_metrics_output.push_str("\n");
+        custom_metrics_output.push('\n');
         custom_metrics_output.push_str(TYPE_MONITOR_CERT_DAYS_REMAINING);
-        custom_metrics_output.push_str("\n");
+        custom_metrics_output.push('\n');
 
         custom_metrics_output.push_str(HELP_MONITOR_CERT_IS_VALID);
-        custom_metrics_output.push_str("\n");
+        custom_metrics_output.push('\n');
         custom_metrics_output.push_str(TYPE_MONITOR_CERT_IS_VALID);
-        custom_metrics_output.push_str("\n");
+        custom_metrics_output.push('\n');
 
         custom_metrics_output.push_str(&http_metrics_buffer);
     }
 
+    if !websocket_metrics_buffer.is_empty() {
+        custom_metrics_output.push_str(HELP_MONITOR_WEBSOCKET_HANDSHAKE_TIME);
+        custom_metrics_output.push('\n');
+        custom_metrics_output.push_str(TYPE_MONITOR_WEBSOCKET_HANDSHAKE_TIME);
+        custom_metrics_output.push('\n');
+        custom_metrics_output.push_str(&websocket_metrics_buffer);
+    }
+
+    if !websocket_rtt_buffer.is_empty() {
+        custom_metrics_output.push_str(HELP_MONITOR_WEBSOCKET_MESSAGE_RTT);
+        custom_metrics_output.push('\n');
+        custom_metrics_output.push_str(TYPE_MONITOR_WEBSOCKET_MESSAGE_RTT);
+        custom_metrics_output.push('\n');
+        custom_metrics_output.push_str(&websocket_rtt_buffer);
+    }
+
+    if !http_phase_buffer.is_empty() {
+        custom_metrics_output.push_str(HELP_MONITOR_HTTP_PHASE_TIME);
+        custom_metrics_output.push('\n');
+        custom_metrics_output.push_str(TYPE_MONITOR_HTTP_PHASE_TIME);
+        custom_metrics_output.push('\n');
+        custom_metrics_output.push_str(&http_phase_buffer);
+    }
+
+    if !address_status_buffer.is_empty() {
+        custom_metrics_output.push_str(HELP_MONITOR_ADDRESS_STATUS);
+        custom_metrics_output.push('\n');
+        custom_metrics_output.push_str(TYPE_MONITOR_ADDRESS_STATUS);
+        custom_metrics_output.push('\n');
+        custom_metrics_output.push_str(&address_status_buffer);
+
+        custom_metrics_output.push_str(HELP_MONITOR_ADDRESS_RESPONSE_TIME);
+        custom_metrics_output.push('\n');
+        custom_metrics_output.push_str(TYPE_MONITOR_ADDRESS_RESPONSE_TIME);
+        custom_metrics_output.push('\n');
+        custom_metrics_output.push_str(&address_response_time_buffer);
+    }
+
+    if !oauth2_token_buffer.is_empty() {
+        custom_metrics_output.push_str(HELP_MONITOR_OAUTH2_TOKEN_STATUS);
+        custom_metrics_output.push('\n');
+        custom_metrics_output.push_str(TYPE_MONITOR_OAUTH2_TOKEN_STATUS);
+        custom_metrics_output.push('\n');
+        custom_metrics_output.push_str(&oauth2_token_buffer);
+    }
+
     // Process Metrics
     let registry = Registry::new();
     let process_collector = ProcessCollector::for_self();
@@ -449,11 +618,14 @@ pub async fn start_web_server(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::monitoring::{CheckStatus, HttpCheckResultDetails, TcpCheckResult};
+    use crate::monitoring::{
+        AddressCheckResult, CheckStatus, HttpCheckResultDetails, HttpPhaseTimings, TcpCheckResult,
+    };
     use actix_web::body::to_bytes;
     use actix_web::test as actix_test; // Renamed to avoid conflict
 
     // Helper to create a TargetStatus for testing
+    #[allow(clippy::too_many_arguments)]
     fn create_test_target_status(
         alias: &str,
         is_healthy: bool,
@@ -533,6 +705,18 @@ mod tests {
                     response_time_ms: 120,
                     cert_days_remaining: Some(30),
                     cert_is_valid: Some(true),
+                    redirects: Vec::new(),
+                    timings: Some(HttpPhaseTimings {
+                        dns_ms: 5,
+                        connect_ms: 20,
+                        tls_ms: Some(40),
+                        ttfb_ms: 50,
+                        download_ms: 5,
+                        connection_reused: false,
+                    }),
+                    addresses: Vec::new(),
+                    oauth2_token_ok: None,
+                    assertions: Vec::new(),
                 })),
                 Some(30),
                 Some(true),
@@ -549,6 +733,22 @@ mod tests {
                     response_time_ms: 5000,
                     cert_days_remaining: None,
                     cert_is_valid: None,
+                    redirects: Vec::new(),
+                    timings: None,
+                    addresses: vec![
+                        AddressCheckResult {
+                            address: "192.0.2.10".to_string(),
+                            status: CheckStatus::Healthy,
+                            response_time_ms: 80,
+                        },
+                        AddressCheckResult {
+                            address: "2001:db8::10".to_string(),
+                            status: CheckStatus::Unhealthy("Timeout".to_string()),
+                            response_time_ms: 5000,
+                        },
+                    ],
+                    oauth2_token_ok: Some(true),
+                    assertions: Vec::new(),
                 })),
                 None,
                 None,
@@ -560,7 +760,10 @@ mod tests {
                 "tcp://healthy.tcp.example.com:1234",
                 "healthy.tcp.example.com",
                 1234,
-                Some(CheckResult::Tcp(TcpCheckResult { result: Ok(()) })),
+                Some(CheckResult::Tcp(TcpCheckResult {
+                    status: CheckStatus::Healthy,
+                    response_time_ms: 10,
+                })),
                 None,
                 None,
             ),
@@ -572,7 +775,8 @@ mod tests {
                 "unhealthy.tcp.example.com",
                 5678,
                 Some(CheckResult::Tcp(TcpCheckResult {
-                    result: Err("Connection refused".to_string()),
+                    status: CheckStatus::Unhealthy("Connection refused".to_string()),
+                    response_time_ms: 0,
                 })),
                 None,
                 None,
@@ -639,6 +843,19 @@ mod tests {
             "monitor_cert_is_valid{{{}}} 1",
             healthy_http_labels
         )));
+        assert_eq!(body_str.matches(HELP_MONITOR_HTTP_PHASE_TIME).count(), 1);
+        for (phase, value) in [
+            ("dns", 5),
+            ("connect", 20),
+            ("tls", 40),
+            ("ttfb", 50),
+            ("download", 5),
+        ] {
+            assert!(body_str.contains(&format!(
+                "monitor_http_phase_time{{{},phase=\"{}\"}} {}",
+                healthy_http_labels, phase, value
+            )));
+        }
 
         // Unhealthy HTTP No Cert
         let unhealthy_http_labels = "monitor_name=\"Unhealthy HTTP No Cert\",monitor_type=\"http\",monitor_url=\"http://unhealthy.example.com\",monitor_hostname=\"unhealthy.example.com\",monitor_port=\"80\"";
@@ -656,10 +873,43 @@ mod tests {
             "monitor_cert_days_remaining{{{}}}",
             unhealthy_http_labels
         )));
+        // No response, so no phase timings
+        assert!(!body_str.contains(&format!(
+            "monitor_http_phase_time{{{},",
+            unhealthy_http_labels
+        )));
         assert!(body_str.contains(&format!(
             "monitor_cert_is_valid{{{}}} 0",
             unhealthy_http_labels
         ))); // Should be 0 as None is treated as false
+             // One series per checked address
+        assert_eq!(body_str.matches(HELP_MONITOR_ADDRESS_STATUS).count(), 1);
+        assert!(body_str.contains(&format!(
+            "monitor_address_status{{{},address=\"192.0.2.10\"}} 1",
+            unhealthy_http_labels
+        )));
+        assert!(body_str.contains(&format!(
+            "monitor_address_status{{{},address=\"2001:db8::10\"}} 0",
+            unhealthy_http_labels
+        )));
+        assert!(body_str.contains(&format!(
+            "monitor_address_response_time{{{},address=\"2001:db8::10\"}} 5000",
+            unhealthy_http_labels
+        )));
+        assert!(!body_str.contains(&format!("monitor_address_status{{{},", healthy_http_labels)));
+        // The token was obtained, so the target itself failed
+        assert_eq!(
+            body_str.matches(HELP_MONITOR_OAUTH2_TOKEN_STATUS).count(),
+            1
+        );
+        assert!(body_str.contains(&format!(
+            "monitor_oauth2_token_status{{{}}} 1",
+            unhealthy_http_labels
+        )));
+        assert!(!body_str.contains(&format!(
+            "monitor_oauth2_token_status{{{}}}",
+            healthy_http_labels
+        )));
 
         // Healthy TCP
         let healthy_tcp_labels = "monitor_name=\"Healthy TCP\",monitor_type=\"tcp\",monitor_url=\"tcp://healthy.tcp.example.com:1234\",monitor_hostname=\"healthy.tcp.example.com\",monitor_port=\"1234\"";
@@ -668,8 +918,11 @@ mod tests {
             "monitor_consecutive_failures{{{}}} 0",
             healthy_tcp_labels
         )));
-        // HTTP specific metrics should NOT be present
-        assert!(!body_str.contains(&format!("monitor_response_time{{{}}}", healthy_tcp_labels)));
+        // TCP checks report response time but no certificate metrics
+        assert!(body_str.contains(&format!(
+            "monitor_response_time{{{}}} 10",
+            healthy_tcp_labels
+        )));
         assert!(!body_str.contains(&format!(
             "monitor_cert_days_remaining{{{}}}",
             healthy_tcp_labels
@@ -699,6 +952,11 @@ mod tests {
                 response_time_ms: 75,
                 cert_days_remaining: None, // Explicitly None
                 cert_is_valid: None,       // Explicitly None
+                redirects: Vec::new(),
+                timings: None,
+                addresses: Vec::new(),
+                oauth2_token_ok: None,
+                assertions: Vec::new(),
             })),
             None,
             None, // TargetStatus also has None for cert fields
@@ -738,6 +996,11 @@ mod tests {
                 response_time_ms: 50,
                 cert_days_remaining: None,
                 cert_is_valid: None,
+                redirects: Vec::new(),
+                timings: None,
+                addresses: Vec::new(),
+                oauth2_token_ok: None,
+                assertions: Vec::new(),
             })),
             None,
             None,
@@ -778,6 +1041,11 @@ mod tests {
                 response_time_ms: 150,
                 cert_days_remaining: Some(30),
                 cert_is_valid: Some(true),
+                redirects: Vec::new(),
+                timings: None,
+                addresses: Vec::new(),
+                oauth2_token_ok: None,
+                assertions: Vec::new(),
             }));
         } else {
             status.last_result = Some(CheckResult::Http(HttpCheckResultDetails {
@@ -785,6 +1053,11 @@ mod tests {
                 response_time_ms: 5000,
                 cert_days_remaining: None,
                 cert_is_valid: Some(false),
+                redirects: Vec::new(),
+                timings: None,
+                addresses: Vec::new(),
+                oauth2_token_ok: None,
+                assertions: Vec::new(),
             }));
         }
 
@@ -3,181 +3,22 @@ use std::io::{Read, Write};
 use std::net::TcpStream;
 use std::net::ToSocketAddrs;
 use std::time::Instant;
-
-#[cfg(not(target_os = "windows"))]
-mod tls_ffi {
-    use std::ffi::CString;
-    use std::net::TcpStream;
-    use std::os::fd::FromRawFd;
-    use std::os::raw::{c_int, c_long, c_void};
-    use std::os::unix::io::IntoRawFd;
-
-    // SSL_MODE_RELEASE_BUFFERS — free read/write buffers when idle
-    pub const SSL_MODE_RELEASE_BUFFERS: c_long = 0x00000010;
-    // SSL_CTRL_SET_TLSEXT_HOSTNAME = 55 (SSL_set_tlsext_host_name macro)
-    pub const SSL_CTRL_SET_TLSEXT_HOSTNAME: c_int = 55;
-    // SSL_CTX_set_mode cmd = 33
-    pub const SSL_CTRL_MODE: c_int = 33;
-
-    #[repr(C)]
-    pub struct SSL_CTX(c_void);
-    #[repr(C)]
-    pub struct SSL(c_void);
-    #[repr(C)]
-    pub struct SSL_METHOD(c_void);
-
-    #[link(name = "ssl")]
-    #[link(name = "crypto")]
-    extern "C" {
-        pub fn TLS_client_method() -> *const SSL_METHOD;
-        pub fn SSL_CTX_new(method: *const SSL_METHOD) -> *mut SSL_CTX;
-        pub fn SSL_CTX_free(ctx: *mut SSL_CTX);
-        pub fn SSL_CTX_ctrl(
-            ctx: *mut SSL_CTX,
-            cmd: c_int,
-            larg: c_long,
-            parg: *mut c_void,
-        ) -> c_long;
-        pub fn SSL_new(ctx: *mut SSL_CTX) -> *mut SSL;
-        pub fn SSL_free(ssl: *mut SSL);
-        pub fn SSL_set_fd(ssl: *mut SSL, fd: c_int) -> c_int;
-        pub fn SSL_connect(ssl: *mut SSL) -> c_int;
-        pub fn SSL_read(ssl: *mut SSL, buf: *mut c_void, num: c_int) -> c_int;
-        pub fn SSL_write(ssl: *mut SSL, buf: *const c_void, num: c_int) -> c_int;
-        // SSL_set_tlsext_host_name / SSL_set_mode are macros over SSL_ctrl
-        pub fn SSL_ctrl(ssl: *mut SSL, cmd: c_int, larg: c_long, parg: *mut c_void) -> c_long;
-        pub fn SSL_CTX_set_verify(ctx: *mut SSL_CTX, mode: c_int, cb: *const c_void);
-        pub fn SSL_CTX_set_default_verify_paths(ctx: *mut SSL_CTX) -> c_int;
-        pub fn SSL_get_error(ssl: *const SSL, ret: c_int) -> c_int;
-    }
-
-    pub struct TlsStream {
-        pub ssl: *mut SSL,
-        pub ctx: *mut SSL_CTX,
-        _tcp: std::mem::ManuallyDrop<TcpStream>,
-    }
-
-    impl TlsStream {
-        pub fn new(tcp: TcpStream, hostname: &str) -> Result<Self, String> {
-            unsafe {
-                let method = TLS_client_method();
-                if method.is_null() {
-                    return Err("TLS_client_method failed".into());
-                }
-
-                let ctx = SSL_CTX_new(method);
-                if ctx.is_null() {
-                    return Err("SSL_CTX_new failed".into());
-                }
-
-                SSL_CTX_set_default_verify_paths(ctx);
-                SSL_CTX_set_verify(ctx, 1, std::ptr::null());
-                // SSL_CTX_set_mode(ctx, SSL_MODE_RELEASE_BUFFERS)
-                SSL_CTX_ctrl(
-                    ctx,
-                    SSL_CTRL_MODE,
-                    SSL_MODE_RELEASE_BUFFERS,
-                    std::ptr::null_mut(),
-                );
-
-                let ssl = SSL_new(ctx);
-                if ssl.is_null() {
-                    SSL_CTX_free(ctx);
-                    return Err("SSL_new failed".into());
-                }
-
-                // SNI
-                let chost = CString::new(hostname).map_err(|e| e.to_string())?;
-                SSL_ctrl(
-                    ssl,
-                    SSL_CTRL_SET_TLSEXT_HOSTNAME,
-                    0,
-                    chost.as_ptr() as *mut c_void,
-                );
-
-                let fd = tcp.into_raw_fd();
-                SSL_set_fd(ssl, fd);
-
-                let ret = SSL_connect(ssl);
-                if ret != 1 {
-                    let err = SSL_get_error(ssl, ret);
-                    SSL_free(ssl);
-                    SSL_CTX_free(ctx);
-                    let owned = std::os::unix::io::OwnedFd::from_raw_fd(fd);
-                    drop(owned);
-                    return Err(format!("SSL_connect failed, error code: {}", err));
-                }
-
-                let tcp_back: TcpStream = std::os::unix::io::FromRawFd::from_raw_fd(fd);
-                Ok(TlsStream {
-                    ssl,
-                    ctx,
-                    _tcp: std::mem::ManuallyDrop::new(tcp_back),
-                })
-            }
-        }
-
-        pub fn write_all(&mut self, data: &[u8]) -> Result<(), String> {
-            let mut written = 0;
-            while written < data.len() {
-                let ret = unsafe {
-                    SSL_write(
-                        self.ssl,
-                        data[written..].as_ptr() as *const c_void,
-                        (data.len() - written) as c_int,
-                    )
-                };
-                if ret <= 0 {
-                    return Err(format!("SSL_write failed: {}", ret));
-                }
-                written += ret as usize;
-            }
-            Ok(())
-        }
-
-        pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, String> {
-            let ret = unsafe {
-                SSL_read(
-                    self.ssl,
-                    buf.as_mut_ptr() as *mut c_void,
-                    buf.len() as c_int,
-                )
-            };
-            if ret < 0 {
-                Err(format!("SSL_read failed: {}", ret))
-            } else {
-                Ok(ret as usize)
-            }
-        }
-    }
-
-    impl Drop for TlsStream {
-        fn drop(&mut self) {
-            unsafe {
-                SSL_free(self.ssl);
-                SSL_CTX_free(self.ctx);
-                std::mem::ManuallyDrop::drop(&mut self._tcp);
-            }
-        }
-    }
-}
+use uptime_monitor::config::TlsClientConfig;
+use uptime_monitor::monitoring::tls::connect_tls;
 
 // Returns (t_ssl, t_ttfb, t_total, header_buf)
-#[cfg(not(target_os = "windows"))]
 fn do_https(
     tcp: TcpStream,
     host: &str,
     request: &str,
     t_start: Instant,
 ) -> Result<(f64, f64, f64, Vec<u8>), String> {
-    use tls_ffi::TlsStream;
-
     // Disable Nagle: our HTTP request is small; without TCP_NODELAY the kernel
     // may buffer it waiting for more data, adding ~40ms on some stacks.
     tcp.set_nodelay(true)
         .map_err(|e| format!("set_nodelay failed: {}", e))?;
 
-    let mut tls = TlsStream::new(tcp, host).map_err(|e| format!("TLS handshake failed: {}", e))?;
+    let (mut tls, _) = connect_tls(host, tcp, &TlsClientConfig::default(), true)?;
     let t_ssl = Instant::now().duration_since(t_start).as_secs_f64();
 
     // Send request as single SSL record — no fragmentation
@@ -207,32 +48,17 @@ fn do_https(
     }
 
     // Phase 2: drain body (discarded, like curl -o /dev/null) — measures total time
-    loop {
-        let n = tls.read(&mut tmp).unwrap_or(0);
-        if n == 0 {
-            break;
-        }
-    }
+    std::io::copy(&mut tls, &mut std::io::sink()).map_err(|e| format!("Read failed: {}", e))?;
 
     let t_total = Instant::now().duration_since(t_start).as_secs_f64();
     Ok((t_ssl, ttfb_time, t_total, buf))
 }
 
-#[cfg(target_os = "windows")]
-fn do_https(
-    _tcp: TcpStream,
-    _host: &str,
-    _request: &str,
-    _t_start: Instant,
-) -> Result<(f64, f64, f64, Vec<u8>), String> {
-    Err("HTTPS on Windows requires native-tls crate (no zero-dep option)".into())
-}
-
 fn parse_url(url: &str) -> Result<(bool, String, u16, String), String> {
-    let (is_https, rest) = if url.starts_with("https://") {
-        (true, &url[8..])
-    } else if url.starts_with("http://") {
-        (false, &url[7..])
+    let (is_https, rest) = if let Some(rest) = url.strip_prefix("https://") {
+        (true, rest)
+    } else if let Some(rest) = url.strip_prefix("http://") {
+        (false, rest)
     } else {
         return Err("URL must start with http:// or https://".into());
     };
@@ -332,12 +158,7 @@ fn measure_inner(
                 break;
             }
         }
-        loop {
-            let n = tcp.read(&mut tmp).unwrap_or(0);
-            if n == 0 {
-                break;
-            }
-        }
+        std::io::copy(&mut tcp, &mut std::io::sink()).map_err(|e| format!("Read failed: {}", e))?;
 
         t_ttfb = ttfb_time;
         t_total = Instant::now().duration_since(t_start).as_secs_f64();
@@ -358,7 +179,7 @@ fn measure_inner(
         if let Some(loc) = response_str
             .lines()
             .find(|l| l.to_lowercase().starts_with("location:"))
-            .and_then(|l| l.splitn(2, ':').nth(1))
+            .and_then(|l| l.split_once(':').map(|(_, v)| v))
             .map(|s| s.trim().to_string())
         {
             let new_url = if loc.starts_with("http") {
@@ -0,0 +1,631 @@
+use crate::config::{config_schema, AppConfig, Check, HostConfig};
+use crate::report;
+use crate::validate::load_and_validate;
+use std::io::Write;
+
+pub const DEFAULT_CONFIG_FILE_PATH: &str = "config.toml";
+pub const DEFAULT_SERVER_ADDRESS: &str = "0.0.0.0:8080";
+
+pub const USAGE: &str = "\
+Usage: uptime_monitor [OPTIONS] [COMMAND] [CONFIG]
+
+Commands:
+  serve          Monitor the targets and serve metrics and badges (default)
+  validate       Check the configuration and report every problem found
+  check          Run every check once, concurrently, and print the results
+  list-targets   Print the configured targets
+  export         Print the effective configuration, secrets redacted
+  schema         Print the JSON Schema of configuration files, for editors
+
+Options:
+  -c, --config <PATH>        Configuration file (TOML, YAML or JSON) or directory
+                             [env: UPTIME_MONITOR_CONFIG] [default: config.toml]
+  -l, --listen <ADDRESS>     Address to serve on [env: UPTIME_MONITOR_LISTEN] [default: 0.0.0.0:8080]
+      --log-level <LEVEL>    off, error, warn, info, debug, trace or a RUST_LOG filter
+                             [env: UPTIME_MONITOR_LOG_LEVEL, RUST_LOG] [default: info, warn when not serving]
+      --log-format <FORMAT>  text or json [env: UPTIME_MONITOR_LOG_FORMAT] [default: text]
+      --format <FORMAT>      Output of check: table, json, junit or tap [default: table]
+                             Output of export: toml, yaml or json [default: toml]
+      --host <HOST>          Only targets on this host address or alias
+      --tag <TAG>            Only targets on hosts with this tag
+      --name <NAME>          Only the target with this name
+                             (--host, --tag and --name may be repeated)
+  -h, --help                 Show this help
+  -V, --version              Show the version
+
+Exit status of check: 0 when every check is healthy, 1 when any is unhealthy,
+2 on a usage error, an invalid configuration or when no target matches.
+";
+
+const LOG_LEVELS: [&str; 6] = ["off", "error", "warn", "info", "debug", "trace"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Serve,
+    Validate,
+    Check,
+    ListTargets,
+    Export,
+    Schema,
+    Help,
+    Version,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Toml,
+    Yaml,
+    Json,
+    Junit,
+    Tap,
+}
+
+/// Which targets `check` and `list-targets` apply to. A target must match
+/// each list that is not empty, by any of its entries.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TargetFilter {
+    pub hosts: Vec<String>,
+    pub tags: Vec<String>,
+    pub names: Vec<String>,
+}
+
+impl TargetFilter {
+    pub fn matches(&self, host: &HostConfig, check: &Check) -> bool {
+        let host_matches =
+            |wanted: &String| *wanted == host.address || host.alias.as_ref() == Some(wanted);
+        (self.hosts.is_empty() || self.hosts.iter().any(host_matches))
+            && (self.tags.is_empty() || self.tags.iter().any(|tag| host.tags.contains(tag)))
+            && (self.names.is_empty() || self.names.contains(&host.target_alias(check)))
+    }
+
+    /// The matching targets, in configuration order.
+    pub fn select<'a>(&self, config: &'a AppConfig) -> Vec<(&'a HostConfig, &'a Check)> {
+        config
+            .hosts
+            .iter()
+            .flat_map(|host| host.checks.iter().map(move |check| (host, check)))
+            .filter(|(host, check)| self.matches(host, check))
+            .collect()
+    }
+}
+
+/// Parsed command line, with environment variables filled in for flags
+/// that were not given.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cli {
+    pub command: Command,
+    pub config_path: String,
+    pub listen: String,
+    pub log_level: String,
+    pub log_format: LogFormat,
+    pub format: OutputFormat,
+    pub filter: TargetFilter,
+}
+
+/// Parse the arguments after the program name. `env` looks up environment
+/// variables, which flags override. `TEST_CONFIG_PATH` and
+/// `TEST_SERVER_ADDRESS` are still read for existing deployments.
+pub fn parse(
+    args: impl IntoIterator<Item = String>,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<Cli, String> {
+    let mut command = None;
+    let mut config_path = None;
+    let mut listen = None;
+    let mut log_level = None;
+    let mut log_format = None;
+    let mut format = None;
+    let mut filter = TargetFilter::default();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        // `--flag=value` or `--flag value`
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("{} requires a value", name))
+        };
+        match flag.as_str() {
+            "-h" | "--help" => command = Some(Command::Help),
+            "-V" | "--version" => command = Some(Command::Version),
+            "-c" | "--config" => config_path = Some(value("--config")?),
+            "-l" | "--listen" => listen = Some(value("--listen")?),
+            "--log-level" => log_level = Some(value("--log-level")?),
+            "--log-format" => log_format = Some(value("--log-format")?),
+            "--format" => format = Some(value("--format")?),
+            "--host" => filter.hosts.push(value("--host")?),
+            "--tag" => filter.tags.push(value("--tag")?),
+            "--name" => filter.names.push(value("--name")?),
+            flag if flag.starts_with('-') => return Err(format!("Unknown option: {}", flag)),
+            word if command.is_none() => {
+                command = Some(match word {
+                    "serve" => Command::Serve,
+                    "validate" => Command::Validate,
+                    "check" => Command::Check,
+                    "list-targets" => Command::ListTargets,
+                    "export" => Command::Export,
+                    "schema" => Command::Schema,
+                    // `uptime_monitor config.toml` serves that configuration
+                    path if looks_like_path(path) => {
+                        if config_path.is_some() {
+                            return Err(format!("Unexpected argument: {}", path));
+                        }
+                        config_path = Some(path.to_string());
+                        Command::Serve
+                    }
+                    _ => return Err(format!("Unknown command: {}", word)),
+                })
+            }
+            path if config_path.is_none() => config_path = Some(path.to_string()),
+            extra => return Err(format!("Unexpected argument: {}", extra)),
+        }
+    }
+
+    let command = command.unwrap_or(Command::Serve);
+    let log_level = log_level
+        .or_else(|| env("UPTIME_MONITOR_LOG_LEVEL"))
+        .or_else(|| env("RUST_LOG"))
+        .unwrap_or_else(|| match command {
+            Command::Serve => "info".to_string(),
+            _ => "warn".to_string(),
+        });
+    // Anything with a module path or several directives is a RUST_LOG filter
+    if !log_level.contains(['=', ',']) && !LOG_LEVELS.contains(&log_level.to_lowercase().as_str()) {
+        return Err(format!("Invalid log level: {}", log_level));
+    }
+
+    let log_format = match log_format
+        .or_else(|| env("UPTIME_MONITOR_LOG_FORMAT"))
+        .as_deref()
+    {
+        None | Some("text") => LogFormat::Text,
+        Some("json") => LogFormat::Json,
+        Some(other) => {
+            return Err(format!(
+                "Invalid log format: {} (expected text or json)",
+                other
+            ))
+        }
+    };
+    let format = match (command, format.as_deref()) {
+        (Command::Check, None | Some("table")) => OutputFormat::Table,
+        (Command::Export, None | Some("toml")) => OutputFormat::Toml,
+        (Command::Export, Some("yaml")) => OutputFormat::Yaml,
+        (Command::Check | Command::Export, Some("json")) => OutputFormat::Json,
+        (Command::Check, Some("junit")) => OutputFormat::Junit,
+        (Command::Check, Some("tap")) => OutputFormat::Tap,
+        (Command::Check, Some(other)) => {
+            return Err(format!(
+                "Invalid format: {} (expected table, json, junit or tap)",
+                other
+            ))
+        }
+        (Command::Export, Some(other)) => {
+            return Err(format!(
+                "Invalid format: {} (expected toml, yaml or json)",
+                other
+            ))
+        }
+        (_, None) => OutputFormat::Table,
+        (_, Some(_)) => return Err("--format only applies to check and export".to_string()),
+    };
+
+    Ok(Cli {
+        command,
+        config_path: config_path
+            .or_else(|| env("UPTIME_MONITOR_CONFIG"))
+            .or_else(|| env("TEST_CONFIG_PATH"))
+            .unwrap_or_else(|| DEFAULT_CONFIG_FILE_PATH.to_string()),
+        listen: listen
+            .or_else(|| env("UPTIME_MONITOR_LISTEN"))
+            .or_else(|| env("TEST_SERVER_ADDRESS"))
+            .unwrap_or_else(|| DEFAULT_SERVER_ADDRESS.to_string()),
+        log_level,
+        log_format,
+        format,
+        filter,
+    })
+}
+
+// A configuration file has an extension or a directory; a command has neither
+fn looks_like_path(word: &str) -> bool {
+    word.contains(['.', '/', std::path::MAIN_SEPARATOR]) || std::path::Path::new(word).exists()
+}
+
+pub fn init_logging(cli: &Cli) {
+    let mut builder = env_logger::Builder::new();
+    builder.parse_filters(&cli.log_level);
+    if cli.log_format == LogFormat::Json {
+        builder.format(|buf, record| {
+            let line = serde_json::json!({
+                "timestamp": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+                "level": record.level().to_string(),
+                "target": record.target(),
+                "message": record.args().to_string(),
+            });
+            writeln!(buf, "{}", line)
+        });
+    }
+    builder.init();
+}
+
+/// Load and validate the configuration, printing the problems if any.
+fn load(cli: &Cli) -> Option<AppConfig> {
+    match load_and_validate(&cli.config_path) {
+        Ok(config) => Some(config),
+        Err(issues) => {
+            for issue in &issues {
+                eprintln!("{}", issue);
+            }
+            eprintln!("{}: {} problem(s) found", cli.config_path, issues.len());
+            None
+        }
+    }
+}
+
+// ─── Commands ────────────────────────────────────────────────────────────────
+// Each returns the process exit code.
+
+pub fn validate(cli: &Cli) -> i32 {
+    let Some(config) = load(cli) else {
+        return 1;
+    };
+    let checks: usize = config.hosts.iter().map(|host| host.checks.len()).sum();
+    println!(
+        "{} is valid: {} hosts, {} checks",
+        cli.config_path,
+        config.hosts.len(),
+        checks
+    );
+    0
+}
+
+pub async fn check(cli: &Cli) -> i32 {
+    let Some(config) = load(cli) else {
+        return 2;
+    };
+    let targets = cli.filter.select(&config);
+    if targets.is_empty() {
+        eprintln!("No target matches the filter");
+        return 2;
+    }
+    let outcomes = report::run_once(&targets).await;
+    match cli.format {
+        OutputFormat::Json => println!("{}", report::json(&outcomes)),
+        OutputFormat::Junit => print!("{}", report::junit(&outcomes)),
+        OutputFormat::Tap => print!("{}", report::tap(&outcomes)),
+        _ => print!("{}", report::table(&outcomes)),
+    }
+    match outcomes.iter().all(|outcome| outcome.healthy) {
+        true => 0,
+        false => 1,
+    }
+}
+
+pub fn list_targets(cli: &Cli) -> i32 {
+    let Some(config) = load(cli) else {
+        return 1;
+    };
+    let rows: Vec<[String; 3]> = cli
+        .filter
+        .select(&config)
+        .into_iter()
+        .map(|(host, check)| {
+            [
+                host.target_alias(check),
+                check.kind().to_string(),
+                host.monitor_url(check),
+            ]
+        })
+        .collect();
+    let name_width = rows
+        .iter()
+        .map(|row| row[0].len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    let type_width = rows
+        .iter()
+        .map(|row| row[1].len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    println!("{:<name_width$}  {:<type_width$}  URL", "NAME", "TYPE");
+    for [name, kind, url] in &rows {
+        println!("{:<name_width$}  {:<type_width$}  {}", name, kind, url);
+    }
+    0
+}
+
+pub fn export(cli: &Cli) -> i32 {
+    let Some(config) = load(cli) else {
+        return 1;
+    };
+    let exported = match cli.format {
+        OutputFormat::Json => serde_json::to_string_pretty(&config).map_err(|e| e.to_string()),
+        OutputFormat::Yaml => serde_yaml::to_string(&config).map_err(|e| e.to_string()),
+        _ => toml::to_string_pretty(&config).map_err(|e| e.to_string()),
+    };
+    match exported {
+        Ok(text) => {
+            println!("{}", text.trim_end());
+            0
+        }
+        Err(e) => {
+            eprintln!("Cannot export configuration: {}", e);
+            1
+        }
+    }
+}
+
+pub fn schema() -> i32 {
+    match serde_json::to_string_pretty(&config_schema()) {
+        Ok(text) => {
+            println!("{}", text);
+            0
+        }
+        Err(e) => {
+            eprintln!("Cannot print the schema: {}", e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn parse_with_env(args: &[&str], env: &[(&str, &str)]) -> Result<Cli, String> {
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        parse(args.iter().map(|a| a.to_string()), |name| {
+            env.get(name).cloned()
+        })
+    }
+
+    #[test]
+    fn test_defaults_to_serve() {
+        let cli = parse_with_env(&[], &[]).unwrap();
+        assert_eq!(cli.command, Command::Serve);
+        assert_eq!(cli.config_path, DEFAULT_CONFIG_FILE_PATH);
+        assert_eq!(cli.listen, DEFAULT_SERVER_ADDRESS);
+        assert_eq!(cli.log_level, "info");
+        assert_eq!(cli.log_format, LogFormat::Text);
+        assert_eq!(cli.format, OutputFormat::Table);
+        assert_eq!(cli.filter, TargetFilter::default());
+
+        // One-shot commands keep stderr quiet
+        assert_eq!(parse_with_env(&["check"], &[]).unwrap().log_level, "warn");
+    }
+
+    #[test]
+    fn test_config_without_command_serves_it() {
+        let cli = parse_with_env(&["config.toml"], &[]).unwrap();
+        assert_eq!(cli.command, Command::Serve);
+        assert_eq!(cli.config_path, "config.toml");
+
+        let cli =
+            parse_with_env(&["--listen", "127.0.0.1:9000", "/etc/uptime/conf.d"], &[]).unwrap();
+        assert_eq!(cli.command, Command::Serve);
+        assert_eq!(cli.config_path, "/etc/uptime/conf.d");
+        assert_eq!(cli.listen, "127.0.0.1:9000");
+
+        assert_eq!(
+            parse_with_env(&["config.toml", "other.toml"], &[]).unwrap_err(),
+            "Unexpected argument: other.toml"
+        );
+        assert_eq!(
+            parse_with_env(&["-c", "a.toml", "b.toml"], &[]).unwrap_err(),
+            "Unexpected argument: b.toml"
+        );
+    }
+
+    #[test]
+    fn test_parses_commands_and_flags() {
+        let cli = parse_with_env(
+            &[
+                "--log-format=json",
+                "export",
+                "-c",
+                "/etc/monitor.toml",
+                "--format",
+                "json",
+            ],
+            &[],
+        )
+        .unwrap();
+        assert_eq!(cli.command, Command::Export);
+        assert_eq!(cli.config_path, "/etc/monitor.toml");
+        assert_eq!(cli.log_format, LogFormat::Json);
+        assert_eq!(cli.format, OutputFormat::Json);
+
+        let cli = parse_with_env(&["validate", "other.toml"], &[]).unwrap();
+        assert_eq!(cli.command, Command::Validate);
+        assert_eq!(cli.config_path, "other.toml");
+
+        let cli = parse_with_env(
+            &[
+                "check",
+                "--host",
+                "db",
+                "--tag=prod",
+                "--tag",
+                "eu",
+                "--name",
+                "API",
+            ],
+            &[],
+        )
+        .unwrap();
+        assert_eq!(cli.command, Command::Check);
+        assert_eq!(cli.format, OutputFormat::Table);
+        assert_eq!(
+            cli.filter,
+            TargetFilter {
+                hosts: vec!["db".to_string()],
+                tags: vec!["prod".to_string(), "eu".to_string()],
+                names: vec!["API".to_string()],
+            }
+        );
+
+        let cli = parse_with_env(&["serve", "--listen", "127.0.0.1:9000"], &[]).unwrap();
+        assert_eq!(cli.listen, "127.0.0.1:9000");
+
+        assert_eq!(
+            parse_with_env(&["list-targets"], &[]).unwrap().command,
+            Command::ListTargets
+        );
+        assert_eq!(
+            parse_with_env(&["export", "--format=yaml"], &[])
+                .unwrap()
+                .format,
+            OutputFormat::Yaml
+        );
+        assert_eq!(
+            parse_with_env(&["schema"], &[]).unwrap().command,
+            Command::Schema
+        );
+        assert_eq!(
+            parse_with_env(&["check", "-h"], &[]).unwrap().command,
+            Command::Help
+        );
+        assert_eq!(
+            parse_with_env(&["-V"], &[]).unwrap().command,
+            Command::Version
+        );
+    }
+
+    #[test]
+    fn test_flags_override_environment() {
+        let env = [
+            ("UPTIME_MONITOR_CONFIG", "/env/config.toml"),
+            ("UPTIME_MONITOR_LISTEN", "127.0.0.1:7000"),
+            ("UPTIME_MONITOR_LOG_FORMAT", "json"),
+            ("RUST_LOG", "uptime_monitor=debug"),
+        ];
+        let cli = parse_with_env(&[], &env).unwrap();
+        assert_eq!(cli.config_path, "/env/config.toml");
+        assert_eq!(cli.listen, "127.0.0.1:7000");
+        assert_eq!(cli.log_level, "uptime_monitor=debug");
+        assert_eq!(cli.log_format, LogFormat::Json);
+
+        let cli = parse_with_env(&["-c", "flag.toml", "--log-level", "trace"], &env).unwrap();
+        assert_eq!(cli.config_path, "flag.toml");
+        assert_eq!(cli.log_level, "trace");
+
+        let legacy = [
+            ("TEST_CONFIG_PATH", "/legacy.toml"),
+            ("TEST_SERVER_ADDRESS", "0.0.0.0:1"),
+        ];
+        let cli = parse_with_env(&[], &legacy).unwrap();
+        assert_eq!(cli.config_path, "/legacy.toml");
+        assert_eq!(cli.listen, "0.0.0.0:1");
+    }
+
+    #[test]
+    fn test_rejects_bad_arguments() {
+        let error = |args: &[&str]| parse_with_env(args, &[]).unwrap_err();
+        assert_eq!(error(&["frobnicate"]), "Unknown command: frobnicate");
+        assert_eq!(error(&["--verbose"]), "Unknown option: --verbose");
+        assert_eq!(error(&["--config"]), "--config requires a value");
+        assert_eq!(
+            error(&["validate", "a.toml", "b.toml"]),
+            "Unexpected argument: b.toml"
+        );
+        assert_eq!(error(&["--log-level", "loud"]), "Invalid log level: loud");
+        assert!(error(&["--log-format", "xml"]).starts_with("Invalid log format: xml"));
+        assert!(error(&["export", "--format", "xml"]).starts_with("Invalid format: xml"));
+        assert!(error(&["check", "--format", "toml"]).starts_with("Invalid format: toml"));
+        assert_eq!(
+            parse_with_env(&["check", "--format", "junit"], &[])
+                .unwrap()
+                .format,
+            OutputFormat::Junit
+        );
+        assert_eq!(
+            parse_with_env(&["check", "--format=tap"], &[])
+                .unwrap()
+                .format,
+            OutputFormat::Tap
+        );
+        assert_eq!(
+            error(&["serve", "--format", "json"]),
+            "--format only applies to check and export"
+        );
+    }
+
+    #[test]
+    fn test_filter_selects_by_host_tag_and_name() {
+        let config: AppConfig = toml::from_str(
+            r#"
+[[hosts]]
+address = "db.internal"
+alias = "Database"
+tags = ["production", "eu"]
+  [[hosts.checks]]
+  type = "Tcp"
+  port = 5432
+  [[hosts.checks]]
+  type = "Tcp"
+  name = "SSH"
+  port = 22
+
+[[hosts]]
+address = "staging.internal"
+tags = ["staging"]
+  [[hosts.checks]]
+  type = "Tcp"
+  port = 5432
+"#,
+        )
+        .unwrap();
+        let selected = |filter: TargetFilter| -> Vec<String> {
+            filter
+                .select(&config)
+                .into_iter()
+                .map(|(host, check)| host.target_alias(check))
+                .collect()
+        };
+        let strings = |values: &[&str]| values.iter().map(|v| v.to_string()).collect();
+
+        assert_eq!(selected(TargetFilter::default()).len(), 3);
+        assert_eq!(
+            selected(TargetFilter {
+                hosts: strings(&["Database"]),
+                ..Default::default()
+            }),
+            ["Database (TCP:5432)", "SSH"]
+        );
+        assert_eq!(
+            selected(TargetFilter {
+                tags: strings(&["staging", "eu"]),
+                names: strings(&["SSH", "staging.internal (TCP:5432)"]),
+                ..Default::default()
+            }),
+            ["SSH", "staging.internal (TCP:5432)"]
+        );
+        assert!(selected(TargetFilter {
+            hosts: strings(&["db.internal"]),
+            tags: strings(&["staging"]),
+            ..Default::default()
+        })
+        .is_empty());
+    }
+}
@@ -0,0 +1,422 @@
+use crate::config::{HostConfig, ProxyConfig};
+use crate::secrets;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// One file of a configuration split with `include` or a `conf.d` directory.
+#[derive(Debug, Clone)]
+pub struct ConfigFile {
+    pub path: PathBuf,
+    /// The file as written, secrets unresolved.
+    pub raw: toml::Value,
+    /// The file with secrets resolved and `include` removed.
+    pub resolved: toml::Value,
+    /// The hosts it contributes, as indices into the merged `hosts`.
+    pub hosts: Range<usize>,
+}
+
+/// The syntax of a configuration file, chosen by its extension; TOML unless it
+/// is `.yaml`, `.yml` or `.json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl Format {
+    pub fn of(path: &Path) -> Format {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => Format::Yaml,
+            Some("json") => Format::Json,
+            _ => Format::Toml,
+        }
+    }
+
+    fn parse<T: DeserializeOwned>(self, contents: &str) -> Result<T, String> {
+        match self {
+            Format::Toml => toml::from_str(contents).map_err(|e| e.to_string()),
+            Format::Yaml => serde_yaml::from_str(contents).map_err(|e| e.to_string()),
+            Format::Json => serde_json::from_str(contents).map_err(|e| e.to_string()),
+        }
+    }
+
+    // The file as a TOML value, which the rest of loading works on. TOML has
+    // no null, so null values in YAML and JSON tables count as not set.
+    fn parse_value(self, contents: &str) -> Result<toml::Value, String> {
+        if self == Format::Toml {
+            return self.parse(contents);
+        }
+        let mut value: serde_json::Value = self.parse(contents)?;
+        remove_nulls(&mut value);
+        toml::Value::try_from(value).map_err(|e| e.to_string())
+    }
+}
+
+fn remove_nulls(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.retain(|_, value| !value.is_null());
+            map.values_mut().for_each(remove_nulls);
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(remove_nulls),
+        _ => {}
+    }
+}
+
+// What a single file may contain, checked against the file with its secrets
+// resolved: built from the file as written, its `Secret`s would hold the
+// `env:` and `file:` references and have them scrubbed from every message.
+// The merged configuration is checked again.
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct ConfigFragment {
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    hosts: Vec<HostConfig>,
+    monitoring_interval_seconds: Option<u64>,
+    memory_cleanup_interval_minutes: Option<u64>,
+    keep_history_hours: Option<u64>,
+    proxy: Option<ProxyConfig>,
+}
+
+/// Read the configuration at `path` and every file it includes, in order: a
+/// file comes before the files it includes. A directory stands for the
+/// configuration files in it, sorted by name.
+pub fn load_files(path: &Path) -> Result<Vec<ConfigFile>, String> {
+    let mut files = Vec::new();
+    let mut seen = HashSet::new();
+    if path.is_dir() {
+        for file in config_files_in(path)? {
+            load_file(&file, &mut files, &mut seen)?;
+        }
+    } else {
+        load_file(path, &mut files, &mut seen)?;
+    }
+    Ok(files)
+}
+
+fn load_file(
+    path: &Path,
+    files: &mut Vec<ConfigFile>,
+    seen: &mut HashSet<PathBuf>,
+) -> Result<(), String> {
+    // A file matched by several patterns, or included in a cycle, is read once
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if !seen.insert(canonical) {
+        return Ok(());
+    }
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        format!(
+            "Failed to read configuration file '{}': {}",
+            path.display(),
+            e
+        )
+    })?;
+    let format = Format::of(path);
+    let parse_error = |e: String| {
+        format!(
+            "Failed to parse configuration file '{}': {}",
+            path.display(),
+            e
+        )
+    };
+    let raw = format.parse_value(&contents).map_err(parse_error)?;
+
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    let mut resolved = raw.clone();
+    secrets::resolve(&mut resolved, base_dir).map_err(|e| {
+        format!(
+            "Failed to resolve secrets in configuration file '{}': {}",
+            path.display(),
+            e
+        )
+    })?;
+    let fragment =
+        ConfigFragment::deserialize(resolved.clone()).map_err(|e| parse_error(e.to_string()))?;
+    if let toml::Value::Table(table) = &mut resolved {
+        table.remove("include");
+    }
+    files.push(ConfigFile {
+        path: path.to_path_buf(),
+        raw,
+        resolved,
+        hosts: 0..0,
+    });
+
+    for pattern in &fragment.include {
+        for included in expand(base_dir, pattern, path)? {
+            load_file(&included, files, seen)?;
+        }
+    }
+    Ok(())
+}
+
+/// The files matching an `include` pattern, relative to the including file.
+/// A pattern without wildcards must name an existing file or directory.
+fn expand(base_dir: &Path, pattern: &str, included_by: &Path) -> Result<Vec<PathBuf>, String> {
+    let full = base_dir.join(pattern);
+    if !pattern.contains(['*', '?', '[']) {
+        if full.is_dir() {
+            return config_files_in(&full);
+        }
+        if !full.is_file() {
+            return Err(format!(
+                "File '{}' included by '{}' not found",
+                full.display(),
+                included_by.display()
+            ));
+        }
+        return Ok(vec![full]);
+    }
+    let invalid = |e: String| {
+        format!(
+            "Invalid include pattern '{}' in '{}': {}",
+            pattern,
+            included_by.display(),
+            e
+        )
+    };
+    let mut matches: Vec<PathBuf> = glob::glob(&full.to_string_lossy())
+        .map_err(|e| invalid(e.to_string()))?
+        .collect::<Result<_, _>>()
+        .map_err(|e| invalid(e.to_string()))?;
+    matches.retain(|path| path.is_file());
+    matches.sort();
+    Ok(matches)
+}
+
+fn config_files_in(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        format!(
+            "Failed to read configuration directory '{}': {}",
+            dir.display(),
+            e
+        )
+    })?;
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            let extension = path.extension().and_then(|ext| ext.to_str());
+            path.is_file() && matches!(extension, Some("toml" | "yaml" | "yml" | "json"))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Merge the files into one configuration: their `hosts` are concatenated,
+/// and any other setting may be made by one file only.
+pub fn merge(files: &mut [ConfigFile]) -> Result<toml::Value, String> {
+    let mut merged = toml::Table::new();
+    let mut hosts = Vec::new();
+    let mut has_hosts = false;
+    let mut set_by: Vec<(String, PathBuf)> = Vec::new();
+    for file in files.iter_mut() {
+        let start = hosts.len();
+        let toml::Value::Table(table) = &file.resolved else {
+            continue;
+        };
+        for (key, value) in table {
+            if key == "hosts" {
+                has_hosts = true;
+                if let toml::Value::Array(file_hosts) = value {
+                    hosts.extend(file_hosts.iter().cloned());
+                }
+                continue;
+            }
+            if let Some((_, first)) = set_by.iter().find(|(set, _)| set == key) {
+                return Err(format!(
+                    "'{}' is set in both '{}' and '{}'",
+                    key,
+                    first.display(),
+                    file.path.display()
+                ));
+            }
+            set_by.push((key.clone(), file.path.clone()));
+            merged.insert(key.clone(), value.clone());
+        }
+        file.hosts = start..hosts.len();
+    }
+    // Without hosts anywhere, loading reports the missing field
+    if has_hosts {
+        merged.insert("hosts".to_string(), toml::Value::Array(hosts));
+    }
+    Ok(toml::Value::Table(merged))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, name: &str, contents: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn host(address: &str) -> String {
+        format!("[[hosts]]\naddress = \"{}\"\nchecks = []\n", address)
+    }
+
+    fn file_names(files: &[ConfigFile], dir: &Path) -> Vec<String> {
+        files
+            .iter()
+            .map(|f| f.path.strip_prefix(dir).unwrap().display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_follows_includes_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = write(
+            dir.path(),
+            "config.toml",
+            &format!(
+                "include = [\"hosts/*.toml\", \"extra.toml\"]\nmonitoring_interval_seconds = 30\n{}",
+                host("main.example.com")
+            ),
+        );
+        write(dir.path(), "hosts/b.toml", &host("b.example.com"));
+        write(
+            dir.path(),
+            "hosts/a.toml",
+            &format!("include = [\"../extra.toml\"]\n{}", host("a.example.com")),
+        );
+        write(dir.path(), "hosts/notes.txt", "not configuration");
+        write(dir.path(), "extra.toml", &host("extra.example.com"));
+
+        let mut files = load_files(&main).unwrap();
+        assert_eq!(
+            file_names(&files, dir.path()),
+            [
+                "config.toml",
+                "hosts/a.toml",
+                "hosts/../extra.toml",
+                "hosts/b.toml"
+            ]
+        );
+
+        let merged = merge(&mut files).unwrap();
+        let addresses: Vec<&str> = merged["hosts"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|h| h["address"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            addresses,
+            [
+                "main.example.com",
+                "a.example.com",
+                "extra.example.com",
+                "b.example.com"
+            ]
+        );
+        assert_eq!(merged["monitoring_interval_seconds"].as_integer(), Some(30));
+        assert!(merged.get("include").is_none());
+        assert_eq!(files[0].hosts, 0..1);
+        assert_eq!(files[3].hosts, 3..4);
+    }
+
+    #[test]
+    fn test_directory_mode_reads_toml_files_sorted() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "20-web.toml", &host("web.example.com"));
+        write(
+            dir.path(),
+            "10-global.toml",
+            "monitoring_interval_seconds = 15\n",
+        );
+        write(dir.path(), "README.md", "ignored");
+
+        let files = load_files(dir.path()).unwrap();
+        assert_eq!(
+            file_names(&files, dir.path()),
+            ["10-global.toml", "20-web.toml"]
+        );
+    }
+
+    #[test]
+    fn test_errors_name_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = write(
+            dir.path(),
+            "config.toml",
+            "include = [\"team.toml\"]\nkeep_history_hours = 12\n",
+        );
+
+        write(
+            dir.path(),
+            "team.toml",
+            "[[hosts]]\nalias = \"No address\"\nchecks = []\n",
+        );
+        let error = load_files(&main).unwrap_err();
+        assert!(error.contains("team.toml"), "{}", error);
+        assert!(error.contains("missing field `address`"), "{}", error);
+
+        write(dir.path(), "team.toml", "keep_history_hours = 48\n");
+        let error = merge(&mut load_files(&main).unwrap()).unwrap_err();
+        assert!(
+            error.starts_with("'keep_history_hours' is set in both"),
+            "{}",
+            error
+        );
+        assert!(
+            error.contains("config.toml") && error.contains("team.toml"),
+            "{}",
+            error
+        );
+
+        std::fs::remove_file(dir.path().join("team.toml")).unwrap();
+        let error = load_files(&main).unwrap_err();
+        assert!(error.contains("team.toml' included by"), "{}", error);
+        assert!(error.ends_with("not found"), "{}", error);
+    }
+
+    #[test]
+    fn test_secret_references_are_not_scrubbed() {
+        std::env::set_var("INCLUDES_TEST_PASSWORD", "includes-test-password");
+        let dir = tempfile::tempdir().unwrap();
+        let main = write(
+            dir.path(),
+            "config.toml",
+            r#"
+[[hosts]]
+address = "api.example.com"
+  [[hosts.checks]]
+  type = "Http"
+  port = 443
+  path = "/health"
+  protocol = "Https"
+  method = "Get"
+  auth = { Basic = { username = "monitor", password = "env:INCLUDES_TEST_PASSWORD" } }
+"#,
+        );
+
+        load_files(&main).unwrap();
+        assert_eq!(
+            secrets::scrub("env:INCLUDES_TEST_PASSWORD is includes-test-password"),
+            "env:INCLUDES_TEST_PASSWORD is [REDACTED]"
+        );
+    }
+
+    #[test]
+    fn test_include_cycles_are_read_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = write(dir.path(), "a.toml", "include = [\"b.toml\"]\n");
+        write(
+            dir.path(),
+            "b.toml",
+            &format!("include = [\"a.toml\"]\n{}", host("b")),
+        );
+
+        let files = load_files(&main).unwrap();
+        assert_eq!(file_names(&files, dir.path()), ["a.toml", "b.toml"]);
+    }
+}
@@ -1,41 +1,71 @@
 // Re-export modules for testing
 pub mod api;
+pub mod cli;
 pub mod config;
+pub mod includes;
 pub mod monitoring;
+pub mod report;
+pub mod secrets;
+pub mod validate;
 
 // Application runner function for main and tests
 pub async fn run_app() -> std::io::Result<()> {
+    use crate::cli::Command;
+
+    let cli = match cli::parse(std::env::args().skip(1), |name| std::env::var(name).ok()) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if !matches!(cli.command, Command::Help | Command::Version) {
+        cli::init_logging(&cli);
+    }
+
+    let exit_code = match cli.command {
+        Command::Serve => return serve(cli).await,
+        Command::Validate => cli::validate(&cli),
+        Command::Check => cli::check(&cli).await,
+        Command::ListTargets => cli::list_targets(&cli),
+        Command::Export => cli::export(&cli),
+        Command::Schema => cli::schema(),
+        Command::Help => {
+            print!("{}", cli::USAGE);
+            0
+        }
+        Command::Version => {
+            println!("uptime_monitor {}", env!("CARGO_PKG_VERSION"));
+            0
+        }
+    };
+    std::process::exit(exit_code);
+}
+
+// Monitor the targets and serve metrics and badges until stopped
+async fn serve(cli: cli::Cli) -> std::io::Result<()> {
     use crate::monitoring::TargetStatus;
     use log::{error, info};
     use std::process;
     use std::sync::Arc;
     use tokio::sync::Mutex;
 
-    const DEFAULT_CONFIG_FILE_PATH: &str = "config.toml";
-    const DEFAULT_SERVER_ADDRESS: &str = "0.0.0.0:8080";
-
-    env_logger::init();
     info!("Starting uptime_monitor application");
+    info!("Using configuration file: {}", cli.config_path);
+    info!("Attempting to bind server to: {}", cli.listen);
 
-    let config_path =
-        std::env::var("TEST_CONFIG_PATH").unwrap_or_else(|_| DEFAULT_CONFIG_FILE_PATH.to_string());
-
-    let server_address =
-        std::env::var("TEST_SERVER_ADDRESS").unwrap_or_else(|_| DEFAULT_SERVER_ADDRESS.to_string());
-
-    info!("Using configuration file: {}", config_path);
-    info!("Attempting to bind server to: {}", server_address);
-
-    let loaded_config = match config::load_config(&config_path) {
+    let loaded_config = match validate::load_and_validate(&cli.config_path) {
         Ok(cfg) => {
-            info!("Configuration loaded successfully from {}", config_path);
+            info!("Configuration loaded successfully from {}", cli.config_path);
             cfg
         }
-        Err(_e) => {
-            // Error is already logged by load_config
+        Err(issues) => {
+            for issue in &issues {
+                error!("{}", issue);
+            }
             error!(
                 "Exiting due to configuration load failure from {}.",
-                config_path
+                cli.config_path
             );
             process::exit(1);
         }
@@ -53,11 +83,10 @@ pub async fn run_app() -> std::io::Result<()> {
         monitoring::run_monitoring_loop(app_config_clone, statuses_clone_monitor).await;
     });
 
-    info!("Attempting to start HTTP server on {}", server_address);
-    if let Err(e) =
-        api::start_web_server(server_address.clone(), shared_target_statuses.clone()).await
+    info!("Attempting to start HTTP server on {}", cli.listen);
+    if let Err(e) = api::start_web_server(cli.listen.clone(), shared_target_statuses.clone()).await
     {
-        error!("Failed to start HTTP server on {}: {}", server_address, e);
+        error!("Failed to start HTTP server on {}: {}", cli.listen, e);
         process::exit(1); // Exit if server fails to start
     }
 
@@ -1,7 +1,10 @@
 use crate::config::{
     AssertionPredicate, AssertionQuery, AssertionValue, CertificateField, HttpAssertion,
+    RedirectField,
 };
-use crate::monitoring::types::AssertionResult;
+use crate::monitoring::tls::TlsCertInfo;
+use crate::monitoring::types::{AssertionResult, RedirectHop};
+use crate::monitoring::xpath::evaluate_xpath;
 use http::{HeaderMap, StatusCode};
 use jsonpath_lib as jsonpath;
 use regex::Regex;
@@ -13,7 +16,8 @@ pub fn evaluate_assertions_with_data(
     response_headers: &HeaderMap,
     response_body: &str,
     response_time_ms: u128,
-    _cert_info: Option<()>, // placeholder — cert assertions return None
+    cert_info: Option<&TlsCertInfo>, // Peer certificate, HTTPS/WSS only
+    redirects: &[RedirectHop],       // Followed redirects before the final response
 ) -> Vec<AssertionResult> {
     assertions
         .iter()
@@ -24,6 +28,8 @@ pub fn evaluate_assertions_with_data(
                 response_headers,
                 response_body,
                 response_time_ms,
+                cert_info,
+                redirects,
             )
         })
         .collect()
@@ -35,6 +41,8 @@ fn evaluate_single_assertion_with_data(
     response_headers: &HeaderMap,
     response_body: &str,
     response_time_ms: u128,
+    cert_info: Option<&TlsCertInfo>,
+    redirects: &[RedirectHop],
 ) -> AssertionResult {
     let query_result = match &assertion.query {
         AssertionQuery::Status => Some(serde_json::Value::Number(serde_json::Number::from(
@@ -84,9 +92,32 @@ fn evaluate_single_assertion_with_data(
         AssertionQuery::Duration => Some(serde_json::Value::Number(serde_json::Number::from(
             response_time_ms as u64,
         ))),
-        // Certificate assertions require openssl X509 data; not available in the raw-TCP path.
-        AssertionQuery::Certificate { field: _ } => None,
-        AssertionQuery::XPath { path: _ } => None,
+        AssertionQuery::Certificate { field } => cert_info
+            .and_then(|cert| match field {
+                CertificateField::Subject => cert.subject.clone(),
+                CertificateField::Issuer => cert.issuer.clone(),
+                CertificateField::Serial => cert.serial.clone(),
+                CertificateField::NotBefore => cert.not_before.clone(),
+                CertificateField::NotAfter => cert.not_after.clone(),
+                CertificateField::Algorithm => cert.signature_algorithm.clone(),
+            })
+            .map(serde_json::Value::String),
+        AssertionQuery::XPath { path } => evaluate_xpath(
+            response_body,
+            path,
+            response_headers
+                .get(http::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok()),
+        ),
+        AssertionQuery::RedirectCount => Some(serde_json::Value::Number(serde_json::Number::from(
+            redirects.len(),
+        ))),
+        AssertionQuery::Redirect { hop, field } => redirects.get(*hop).map(|r| match field {
+            RedirectField::Url => serde_json::Value::String(r.url.clone()),
+            RedirectField::Status => serde_json::Value::Number(r.status_code.into()),
+            RedirectField::Location => serde_json::Value::String(r.location.clone()),
+            RedirectField::Duration => serde_json::Value::Number((r.duration_ms as u64).into()),
+        }),
     };
 
     let query_str = format_query(&assertion.query);
@@ -134,7 +165,7 @@ fn evaluate_single_assertion_with_data(
     }
 }
 
-fn evaluate_predicate(
+pub fn evaluate_predicate(
     predicate: &AssertionPredicate,
     actual: &serde_json::Value,
     expected: &AssertionValue,
@@ -199,10 +230,48 @@ where
         (serde_json::Value::Number(a), AssertionValue::Integer(e)) => {
             a.as_f64().map(|v| op(v, *e as f64)).unwrap_or(false)
         }
+        // Dates compare chronologically, e.g. certificate[NotAfter] > "now+20d"
+        (serde_json::Value::String(a), AssertionValue::String(e)) => {
+            match (parse_date_operand(a), parse_date_operand(e)) {
+                (Some(a), Some(e)) => op(a.timestamp() as f64, e.timestamp() as f64),
+                _ => false,
+            }
+        }
         _ => false,
     }
 }
 
+/// Parse an RFC 3339 timestamp or a relative `now[+-]<n><s|m|h|d>` expression.
+pub fn parse_date_operand(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    let value = value.trim();
+    let Some(offset) = value.strip_prefix("now") else {
+        return chrono::DateTime::parse_from_rfc3339(value)
+            .ok()
+            .map(|d| d.with_timezone(&chrono::Utc));
+    };
+
+    let now = chrono::Utc::now();
+    if offset.is_empty() {
+        return Some(now);
+    }
+    let (sign, amount) = match offset.split_at(1) {
+        ("+", rest) => (1, rest),
+        ("-", rest) => (-1, rest),
+        _ => return None,
+    };
+    let unit_pos = amount.len().checked_sub(1)?;
+    let (number, unit) = amount.split_at(unit_pos);
+    let number: i64 = number.parse().ok()?;
+    let delta = match unit {
+        "s" => chrono::Duration::seconds(number),
+        "m" => chrono::Duration::minutes(number),
+        "h" => chrono::Duration::hours(number),
+        "d" => chrono::Duration::days(number),
+        _ => return None,
+    };
+    Some(if sign > 0 { now + delta } else { now - delta })
+}
+
 pub fn string_predicate<F>(actual: &serde_json::Value, expected: &AssertionValue, op: F) -> bool
 where
     F: Fn(&str, &str) -> bool,
@@ -278,6 +347,8 @@ pub fn format_query(query: &AssertionQuery) -> String {
         AssertionQuery::Cookie { name } => format!("cookie[{}]", name),
         AssertionQuery::Duration => "duration".to_string(),
         AssertionQuery::Certificate { field } => format!("certificate[{:?}]", field),
+        AssertionQuery::RedirectCount => "redirects".to_string(),
+        AssertionQuery::Redirect { hop, field } => format!("redirect[{}].{:?}", hop, field),
     }
 }
 
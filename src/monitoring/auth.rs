@@ -1,66 +1,75 @@
+use crate::config::{
+    AddressFamily, ApiKeyLocation, AuthConfig, OAuth2ClientAuth, OAuth2Config, ProxyConfig,
+    TlsClientConfig,
+};
+use crate::monitoring::net::exchange;
+use crate::monitoring::proxy;
+use crate::monitoring::signing::{self, DigestChallenge};
+use crate::monitoring::tls::connect_tls_async;
 use std::collections::HashMap;
-use std::io::{Read, Write};
-use std::net::{TcpStream, ToSocketAddrs};
 use std::sync::Mutex;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant};
 
-// Simple in-memory cache for OAuth2 tokens
-static OAUTH2_TOKEN_CACHE: std::sync::LazyLock<Mutex<HashMap<String, (String, SystemTime)>>> =
+// In-memory cache of OAuth2 tokens and when to stop using them
+static OAUTH2_TOKEN_CACHE: std::sync::LazyLock<Mutex<HashMap<String, (String, Instant)>>> =
     std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
 
-/// Perform a raw HTTP/HTTPS POST to `url` with `application/x-www-form-urlencoded` body.
-/// Returns the full response body as a String.
-fn raw_http_post(url: &str, body: &str) -> Result<String, String> {
-    // Parse URL
-    let (is_https, host, port, path) = parse_url(url)?;
+const TOKEN_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
 
-    let addr_str = format!("{}:{}", host, port);
-    let addrs: Vec<_> = addr_str
-        .to_socket_addrs()
-        .map_err(|e| format!("DNS lookup failed for {}: {}", addr_str, e))?
-        .collect();
-    if addrs.is_empty() {
-        return Err(format!("No addresses resolved for {}", addr_str));
-    }
+/// Lifetime assumed for tokens issued without `expires_in`.
+const DEFAULT_TOKEN_LIFETIME: Duration = Duration::from_secs(3300);
 
-    let tcp = TcpStream::connect_timeout(&addrs[0], Duration::from_secs(10))
-        .map_err(|e| format!("TCP connect failed: {}", e))?;
-    tcp.set_read_timeout(Some(Duration::from_secs(10)))
-        .map_err(|e| format!("set_read_timeout: {}", e))?;
-    tcp.set_nodelay(true).ok();
+/// Longest a token is refreshed ahead of its expiry.
+const MAX_REFRESH_MARGIN: Duration = Duration::from_secs(60);
 
-    let request = format!(
-        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/x-www-form-urlencoded\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
-        path, host, body.len(), body
+/// Perform an HTTP/HTTPS POST to `url` with an `application/x-www-form-urlencoded` body,
+/// through `proxy` unless it exempts the token endpoint.
+/// Returns the full response as a String. Token endpoints must present a
+/// certificate that verifies.
+async fn raw_http_post(
+    url: &str,
+    body: &str,
+    authorization: Option<&str>,
+    proxy: Option<&ProxyConfig>,
+) -> Result<String, String> {
+    let (is_https, host, port, path) = parse_url(url)?;
+    let proxy = proxy::select(proxy, &host, port)?;
+    let forward = proxy.as_ref().filter(|proxy| proxy.forwards(is_https));
+    let mut request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/x-www-form-urlencoded\r\nContent-Length: {}\r\nConnection: close\r\n",
+        if forward.is_some() { url } else { &path }, host, body.len()
     );
-
-    let full_response = if is_https {
-        raw_https_exchange(tcp, &host, &request)?
-    } else {
-        let mut tcp = tcp;
-        tcp.write_all(request.as_bytes())
-            .map_err(|e| format!("Write failed: {}", e))?;
-        let mut buf = Vec::with_capacity(16 * 1024);
-        let mut tmp = [0u8; 4096];
-        loop {
-            let n = tcp.read(&mut tmp).unwrap_or(0);
-            if n == 0 {
-                break;
-            }
-            buf.extend_from_slice(&tmp[..n]);
+    if let Some(value) = authorization {
+        request.push_str(&format!("Authorization: {}\r\n", value));
+    }
+    if let Some(value) = forward.and_then(|proxy| proxy.authorization()) {
+        request.push_str(&format!("Proxy-Authorization: {}\r\n", value));
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+
+    let exchange = async {
+        let transport =
+            proxy::connect(&host, port, is_https, proxy.as_ref(), AddressFamily::Both).await?;
+        if is_https {
+            let (tls, _) =
+                connect_tls_async(&host, transport, &TlsClientConfig::default(), true).await?;
+            exchange(tls, &request).await
+        } else {
+            exchange(transport, &request).await
         }
-        buf
     };
-
-    let response_str = String::from_utf8_lossy(&full_response).into_owned();
-    Ok(response_str)
+    let full_response = tokio::time::timeout(TOKEN_REQUEST_TIMEOUT, exchange)
+        .await
+        .map_err(|_| format!("Request to {} timed out", url))??;
+    Ok(String::from_utf8_lossy(&full_response).into_owned())
 }
 
 fn parse_url(url: &str) -> Result<(bool, String, u16, String), String> {
-    let (is_https, rest) = if url.starts_with("https://") {
-        (true, &url[8..])
-    } else if url.starts_with("http://") {
-        (false, &url[7..])
+    let (is_https, rest) = if let Some(rest) = url.strip_prefix("https://") {
+        (true, rest)
+    } else if let Some(rest) = url.strip_prefix("http://") {
+        (false, rest)
     } else {
         return Err(format!("URL must start with http:// or https://: {}", url));
     };
@@ -81,122 +90,6 @@ fn parse_url(url: &str) -> Result<(bool, String, u16, String), String> {
     Ok((is_https, host, port, path))
 }
 
-#[cfg(not(target_os = "windows"))]
-fn raw_https_exchange(tcp: TcpStream, host: &str, request: &str) -> Result<Vec<u8>, String> {
-    use std::ffi::CString;
-    use std::os::fd::FromRawFd;
-    use std::os::raw::{c_int, c_long, c_void};
-    use std::os::unix::io::IntoRawFd;
-
-    const SSL_CTRL_SET_TLSEXT_HOSTNAME: c_int = 55;
-    const SSL_CTRL_MODE: c_int = 33;
-    const SSL_MODE_RELEASE_BUFFERS: c_long = 0x00000010;
-
-    extern "C" {
-        fn TLS_client_method() -> *const c_void;
-        fn SSL_CTX_new(method: *const c_void) -> *mut c_void;
-        fn SSL_CTX_free(ctx: *mut c_void);
-        fn SSL_CTX_ctrl(ctx: *mut c_void, cmd: c_int, larg: c_long, parg: *mut c_void) -> c_long;
-        fn SSL_new(ctx: *mut c_void) -> *mut c_void;
-        fn SSL_free(ssl: *mut c_void);
-        fn SSL_set_fd(ssl: *mut c_void, fd: c_int) -> c_int;
-        fn SSL_connect(ssl: *mut c_void) -> c_int;
-        fn SSL_read(ssl: *mut c_void, buf: *mut c_void, num: c_int) -> c_int;
-        fn SSL_write(ssl: *mut c_void, buf: *const c_void, num: c_int) -> c_int;
-        fn SSL_ctrl(ssl: *mut c_void, cmd: c_int, larg: c_long, parg: *mut c_void) -> c_long;
-        fn SSL_CTX_set_verify(ctx: *mut c_void, mode: c_int, cb: *const c_void);
-        fn SSL_CTX_set_default_verify_paths(ctx: *mut c_void) -> c_int;
-        fn SSL_get_error(ssl: *const c_void, ret: c_int) -> c_int;
-    }
-
-    unsafe {
-        let method = TLS_client_method();
-        if method.is_null() {
-            return Err("TLS_client_method failed".into());
-        }
-        let ctx = SSL_CTX_new(method);
-        if ctx.is_null() {
-            return Err("SSL_CTX_new failed".into());
-        }
-        SSL_CTX_set_default_verify_paths(ctx);
-        SSL_CTX_set_verify(ctx, 1, std::ptr::null());
-        SSL_CTX_ctrl(
-            ctx,
-            SSL_CTRL_MODE,
-            SSL_MODE_RELEASE_BUFFERS,
-            std::ptr::null_mut(),
-        );
-
-        let ssl = SSL_new(ctx);
-        if ssl.is_null() {
-            SSL_CTX_free(ctx);
-            return Err("SSL_new failed".into());
-        }
-
-        let chost = CString::new(host).map_err(|e| e.to_string())?;
-        SSL_ctrl(
-            ssl,
-            SSL_CTRL_SET_TLSEXT_HOSTNAME,
-            0,
-            chost.as_ptr() as *mut c_void,
-        );
-
-        let fd = tcp.into_raw_fd();
-        SSL_set_fd(ssl, fd);
-
-        let ret = SSL_connect(ssl);
-        if ret != 1 {
-            let err = SSL_get_error(ssl, ret);
-            SSL_free(ssl);
-            SSL_CTX_free(ctx);
-            let owned = std::os::unix::io::OwnedFd::from_raw_fd(fd);
-            drop(owned);
-            return Err(format!("SSL_connect failed: {}", err));
-        }
-
-        // Write
-        let data = request.as_bytes();
-        let mut written = 0;
-        while written < data.len() {
-            let r = SSL_write(
-                ssl,
-                data[written..].as_ptr() as *const c_void,
-                (data.len() - written) as c_int,
-            );
-            if r <= 0 {
-                SSL_free(ssl);
-                SSL_CTX_free(ctx);
-                return Err("SSL_write failed".into());
-            }
-            written += r as usize;
-        }
-
-        // Read
-        let mut buf = Vec::with_capacity(16 * 1024);
-        let mut tmp = [0u8; 4096];
-        loop {
-            let r = SSL_read(ssl, tmp.as_mut_ptr() as *mut c_void, tmp.len() as c_int);
-            if r <= 0 {
-                break;
-            }
-            buf.extend_from_slice(&tmp[..r as usize]);
-        }
-
-        SSL_free(ssl);
-        SSL_CTX_free(ctx);
-
-        // restore TcpStream to drop it properly
-        let _tcp_back: TcpStream = std::os::unix::io::FromRawFd::from_raw_fd(fd);
-
-        Ok(buf)
-    }
-}
-
-#[cfg(target_os = "windows")]
-fn raw_https_exchange(_tcp: TcpStream, _host: &str, _request: &str) -> Result<Vec<u8>, String> {
-    Err("HTTPS on Windows requires native-tls".into())
-}
-
 /// Extract the response body (after \r\n\r\n) from a raw HTTP response string.
 fn extract_body(response: &str) -> &str {
     if let Some(pos) = response.find("\r\n\r\n") {
@@ -240,32 +133,95 @@ fn percent_encode(s: &str) -> String {
     out
 }
 
+/// Tokens differ by client, endpoint and everything requested with them.
+fn cache_key(config: &OAuth2Config) -> String {
+    let extra: Vec<String> = config
+        .extra_params
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect();
+    format!(
+        "{}:{}:{}:{}:{}",
+        config.client_id,
+        config.token_url,
+        config.scopes.join(" "),
+        config.audience.as_deref().unwrap_or(""),
+        extra.join("&")
+    )
+}
+
+/// How long a token can be used: until `expires_in` runs out, less a margin of
+/// a tenth of its lifetime (at most a minute) so it is not sent as it expires.
+fn token_lifetime(response: &serde_json::Value) -> Duration {
+    let expires_in = match response.get("expires_in") {
+        Some(serde_json::Value::Number(n)) => n.as_u64(),
+        Some(serde_json::Value::String(s)) => s.parse().ok(), // Some servers send a string
+        _ => None,
+    };
+    match expires_in {
+        Some(secs) => {
+            let lifetime = Duration::from_secs(secs);
+            lifetime - (lifetime / 10).min(MAX_REFRESH_MARGIN)
+        }
+        None => DEFAULT_TOKEN_LIFETIME,
+    }
+}
+
 pub async fn get_oauth2_token(
-    client_id: &str,
-    client_secret: &str,
-    token_url: &str,
+    config: &OAuth2Config,
+    proxy: Option<&ProxyConfig>,
 ) -> Result<String, String> {
-    let cache_key = format!("{}:{}", client_id, token_url);
+    let cache_key = cache_key(config);
 
     // Check cache first
     {
         let cache = OAUTH2_TOKEN_CACHE.lock().unwrap();
-        if let Some((token, timestamp)) = cache.get(&cache_key) {
-            if timestamp.elapsed().unwrap_or(Duration::from_secs(3900)) < Duration::from_secs(3300)
-            {
+        if let Some((token, expires_at)) = cache.get(&cache_key) {
+            if Instant::now() < *expires_at {
                 return Ok(token.clone());
             }
         }
     }
 
     // Build form body
-    let body = url_encode_form(&[
-        ("grant_type", "client_credentials"),
-        ("client_id", client_id),
-        ("client_secret", client_secret),
-    ]);
+    let scope = config.scopes.join(" ");
+    let mut params = vec![("grant_type", "client_credentials")];
+    if !scope.is_empty() {
+        params.push(("scope", &scope));
+    }
+    if let Some(audience) = &config.audience {
+        params.push(("audience", audience));
+    }
+    params.extend(
+        config
+            .extra_params
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str())),
+    );
+    let authorization = match config.client_auth {
+        OAuth2ClientAuth::Post => {
+            params.push(("client_id", &config.client_id));
+            params.push(("client_secret", config.client_secret.expose()));
+            None
+        }
+        OAuth2ClientAuth::Basic => {
+            // Both parts are form-encoded before Base64 (RFC 6749 §2.3.1)
+            use base64::Engine as _;
+            let credentials = format!(
+                "{}:{}",
+                percent_encode(&config.client_id),
+                percent_encode(config.client_secret.expose())
+            );
+            Some(format!(
+                "Basic {}",
+                base64::engine::general_purpose::STANDARD.encode(credentials)
+            ))
+        }
+    };
+    let body = url_encode_form(&params);
 
-    let response_str = raw_http_post(token_url, &body)?;
+    let response_str =
+        raw_http_post(&config.token_url, &body, authorization.as_deref(), proxy).await?;
     let status = parse_status(&response_str);
 
     if status / 100 != 2 {
@@ -287,8 +243,236 @@ pub async fn get_oauth2_token(
     // Cache it
     {
         let mut cache = OAUTH2_TOKEN_CACHE.lock().unwrap();
-        cache.insert(cache_key, (access_token.to_string(), SystemTime::now()));
+        let expires_at = Instant::now() + token_lifetime(&json);
+        cache.insert(cache_key, (access_token.to_string(), expires_at));
     }
 
     Ok(access_token.to_string())
 }
+
+/// Forget the cached token, e.g. after the target rejected it.
+pub fn invalidate_oauth2_token(config: &OAuth2Config) {
+    OAUTH2_TOKEN_CACHE
+        .lock()
+        .unwrap()
+        .remove(&cache_key(config));
+}
+
+/// Build the header carrying static credentials for `auth`, fetching an OAuth2
+/// token when needed (through `proxy`, like the check itself). Schemes that
+/// sign or answer each request have none; see [`RequestAuth`].
+pub async fn authorization_header(
+    auth: &AuthConfig,
+    proxy: Option<&ProxyConfig>,
+) -> Result<Option<(String, String)>, String> {
+    match auth {
+        AuthConfig::Basic { username, password } => {
+            use base64::Engine as _;
+            let encoded = base64::engine::general_purpose::STANDARD.encode(format!(
+                "{}:{}",
+                username,
+                password.expose()
+            ));
+            Ok(Some(("Authorization".into(), format!("Basic {}", encoded))))
+        }
+        AuthConfig::Bearer { token } => Ok(Some((
+            "Authorization".into(),
+            format!("Bearer {}", token.expose()),
+        ))),
+        AuthConfig::OAuth2(config) => get_oauth2_token(config, proxy)
+            .await
+            .map(|token| Some(("Authorization".into(), format!("Bearer {}", token))))
+            .map_err(|e| format!("OAuth2 authentication failed: {}", e)),
+        AuthConfig::ApiKey {
+            name,
+            value,
+            location: ApiKeyLocation::Header,
+        } => Ok(Some((name.clone(), value.expose().to_string()))),
+        AuthConfig::ApiKey { .. }
+        | AuthConfig::Digest { .. }
+        | AuthConfig::Hmac(_)
+        | AuthConfig::AwsSigV4(_) => Ok(None),
+    }
+}
+
+/// Authentication for the requests of one check: the static header, plus the
+/// signature or Digest response each request needs.
+pub struct RequestAuth<'a> {
+    auth: &'a AuthConfig,
+    header: Option<(String, String)>,
+    digest: Option<DigestChallenge>,
+    nonce_count: u32,
+}
+
+impl<'a> RequestAuth<'a> {
+    pub async fn new(auth: &'a AuthConfig, proxy: Option<&ProxyConfig>) -> Result<Self, String> {
+        Ok(RequestAuth {
+            auth,
+            header: authorization_header(auth, proxy).await?,
+            digest: None,
+            nonce_count: 0,
+        })
+    }
+
+    /// Add an API key that goes in the query string.
+    pub fn apply_to_url(&self, url: &mut url::Url) {
+        if let AuthConfig::ApiKey {
+            name,
+            value,
+            location: ApiKeyLocation::Query,
+        } = self.auth
+        {
+            url.query_pairs_mut().append_pair(name, value.expose());
+        }
+    }
+
+    /// Headers authenticating one request of `method` to `url` with `body`.
+    pub fn headers(
+        &mut self,
+        method: &str,
+        url: &url::Url,
+        body: &[u8],
+    ) -> Result<Vec<(String, String)>, String> {
+        let mut headers: Vec<(String, String)> = self.header.iter().cloned().collect();
+        match self.auth {
+            AuthConfig::Hmac(config) => {
+                headers.extend(signing::hmac_headers(
+                    config,
+                    method,
+                    url,
+                    body,
+                    chrono::Utc::now().timestamp(),
+                )?);
+            }
+            AuthConfig::AwsSigV4(config) => {
+                headers.extend(signing::sigv4_headers(
+                    config,
+                    method,
+                    url,
+                    body,
+                    chrono::Utc::now(),
+                )?);
+            }
+            AuthConfig::Digest { username, password } => {
+                if let Some(challenge) = &self.digest {
+                    self.nonce_count += 1;
+                    headers.push((
+                        "Authorization".into(),
+                        signing::digest_authorization(
+                            username,
+                            password.expose(),
+                            challenge,
+                            method,
+                            &signing::request_target(url),
+                            body,
+                            self.nonce_count,
+                            &signing::cnonce(),
+                        )?,
+                    ));
+                }
+            }
+            _ => {}
+        }
+        Ok(headers)
+    }
+
+    /// Take up the Digest challenge among the `WWW-Authenticate` values of a
+    /// 401. Returns whether the request should be sent again.
+    pub fn accept_challenge<'h>(
+        &mut self,
+        www_authenticate: impl IntoIterator<Item = &'h str>,
+    ) -> bool {
+        if !matches!(self.auth, AuthConfig::Digest { .. }) {
+            return false;
+        }
+        match www_authenticate
+            .into_iter()
+            .find_map(signing::parse_digest_challenge)
+        {
+            Some(challenge) => {
+                self.digest = Some(challenge);
+                self.nonce_count = 0;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_raw_http_post_reads_full_response() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await;
+            let _ = socket
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n{\"access_token\":\"abc\"}",
+                )
+                .await;
+        });
+
+        let response = raw_http_post(
+            &format!("http://127.0.0.1:{}/token", port),
+            "a=b",
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(parse_status(&response), 200);
+        assert_eq!(extract_body(&response), "{\"access_token\":\"abc\"}");
+    }
+
+    #[test]
+    fn test_token_lifetime_follows_expires_in() {
+        let lifetime = |response: serde_json::Value| token_lifetime(&response).as_secs();
+        assert_eq!(lifetime(serde_json::json!({ "expires_in": 300 })), 270);
+        assert_eq!(lifetime(serde_json::json!({ "expires_in": "3600" })), 3540);
+        assert_eq!(lifetime(serde_json::json!({ "expires_in": 0 })), 0);
+        assert_eq!(
+            lifetime(serde_json::json!({})),
+            DEFAULT_TOKEN_LIFETIME.as_secs()
+        );
+    }
+
+    #[test]
+    fn test_cache_key_covers_requested_scope() {
+        let config = OAuth2Config {
+            client_id: "monitor".to_string(),
+            client_secret: "secret".into(),
+            token_url: "https://auth.example.com/token".to_string(),
+            scopes: vec!["read".to_string()],
+            audience: None,
+            extra_params: Default::default(),
+            client_auth: OAuth2ClientAuth::Post,
+        };
+        let mut other = config.clone();
+        other.scopes.push("write".to_string());
+        assert_ne!(cache_key(&config), cache_key(&other));
+        let mut other = config.clone();
+        other.audience = Some("https://api.example.com".to_string());
+        assert_ne!(cache_key(&config), cache_key(&other));
+    }
+
+    #[test]
+    fn test_parse_url() {
+        assert_eq!(
+            parse_url("https://auth.example.com/oauth/token").unwrap(),
+            (true, "auth.example.com".into(), 443, "/oauth/token".into())
+        );
+        assert_eq!(
+            parse_url("http://localhost:8080").unwrap(),
+            (false, "localhost".into(), 8080, "/".into())
+        );
+        assert!(parse_url("ftp://example.com").is_err());
+    }
+}
@@ -1,8 +1,15 @@
-use crate::config::ElasticsearchCheck;
+use crate::config::{AddressFamily, AuthConfig, ElasticsearchCheck, ProxyConfig, TlsClientConfig};
+use crate::monitoring::auth::RequestAuth;
+use crate::monitoring::net::exchange;
+use crate::monitoring::proxy;
+use crate::monitoring::signing::{host_header, request_target};
+use crate::monitoring::tls::connect_tls_async;
+#[cfg(feature = "postgres")]
+use crate::monitoring::tls::{client_configuration, server_name};
 use crate::monitoring::types::{CheckStatus, ServiceCheckResult};
+#[cfg(feature = "postgres")]
+use openssl::ssl::SslVerifyMode;
 
-use std::io::{Read, Write};
-use std::net::{TcpStream, ToSocketAddrs};
 use std::time::{Duration, Instant};
 
 #[cfg(feature = "kafka")]
@@ -25,209 +32,185 @@ struct RawHttpResponse {
     body: String,
 }
 
-fn raw_http_get(
+/// GET `path` from a management/REST API, through `proxy` unless it exempts
+/// the host. The timeout covers the whole exchange, including a second
+/// request answering a Digest challenge.
+async fn raw_http_get(
     address: &str,
     port: u16,
     path: &str,
-    use_ssl: bool,
-    accept_invalid_certs: bool,
-    basic_auth: Option<(&str, &str)>,
+    tls: Option<&TlsClientConfig>,
+    proxy_config: Option<&ProxyConfig>,
+    auth: Option<&AuthConfig>,
     timeout_secs: u64,
 ) -> Result<RawHttpResponse, String> {
-    let addr_str = format!("{}:{}", address, port);
-    let addrs: Vec<_> = addr_str
-        .to_socket_addrs()
-        .map_err(|e| format!("DNS lookup failed: {}", e))?
-        .collect();
-    if addrs.is_empty() {
-        return Err(format!("No addresses resolved for {}", addr_str));
-    }
-
-    let connect_timeout = Duration::from_secs(timeout_secs);
-    let tcp = TcpStream::connect_timeout(&addrs[0], connect_timeout)
-        .map_err(|e| format!("TCP connect failed: {}", e))?;
-    tcp.set_read_timeout(Some(connect_timeout))
-        .map_err(|e| format!("set_read_timeout: {}", e))?;
-    tcp.set_nodelay(true).ok();
-
-    let mut request = format!(
-        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: ruptime/1.0\r\nAccept: application/json\r\nConnection: close\r\n",
-        path, address
-    );
-    if let Some((user, pass)) = basic_auth {
-        use base64::Engine as _;
-        let encoded =
-            base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", user, pass));
-        request.push_str(&format!("Authorization: Basic {}\r\n", encoded));
-    }
-    request.push_str("\r\n");
-
-    let full_response: Vec<u8> = if use_ssl {
-        raw_tls_exchange(tcp, address, &request, accept_invalid_certs)?
+    let is_https = tls.is_some();
+    let host = if address.contains(':') {
+        format!("[{}]", address)
     } else {
-        let mut tcp = tcp;
-        tcp.write_all(request.as_bytes())
-            .map_err(|e| format!("Write failed: {}", e))?;
-        let mut buf = Vec::with_capacity(32 * 1024);
-        let mut tmp = [0u8; 4096];
+        address.to_string()
+    };
+    let mut url = url::Url::parse(&format!(
+        "{}://{}:{}{}",
+        if is_https { "https" } else { "http" },
+        host,
+        port,
+        path
+    ))
+    .map_err(|e| format!("Invalid URL: {}", e))?;
+    let proxy = proxy::select(proxy_config, address, port)?;
+    let forward = proxy.as_ref().filter(|proxy| proxy.forwards(is_https));
+
+    let exchange = async {
+        let mut auth = match auth {
+            Some(auth) => Some(RequestAuth::new(auth, proxy_config).await?),
+            None => None,
+        };
+        if let Some(auth) = &auth {
+            auth.apply_to_url(&mut url);
+        }
+        let target = match forward {
+            Some(_) => url.to_string(),
+            None => request_target(&url),
+        };
+
+        let mut challenged = false;
         loop {
-            let n = tcp.read(&mut tmp).unwrap_or(0);
-            if n == 0 {
-                break;
+            let mut request = format!(
+                "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: ruptime/1.0\r\nAccept: application/json\r\nConnection: close\r\n",
+                target,
+                host_header(&url)
+            );
+            if let Some(auth) = auth.as_mut() {
+                for (k, v) in auth.headers("GET", &url, b"")? {
+                    request.push_str(&format!("{}: {}\r\n", k, v));
+                }
+            }
+            if let Some(value) = forward.and_then(|proxy| proxy.authorization()) {
+                request.push_str(&format!("Proxy-Authorization: {}\r\n", value));
             }
-            buf.extend_from_slice(&tmp[..n]);
+            request.push_str("\r\n");
+
+            let transport =
+                proxy::connect(address, port, is_https, proxy.as_ref(), AddressFamily::Both)
+                    .await?;
+            let raw = match tls {
+                Some(options) => {
+                    let (stream, _) =
+                        connect_tls_async(address, transport, options, options.verify_certificate)
+                            .await?;
+                    exchange(stream, &request).await?
+                }
+                None => exchange(transport, &request).await?,
+            };
+            let (status, headers, body) = split_response(&raw);
+
+            // A Digest challenge is answered by sending the request again
+            if let (401, false, Some(auth)) = (status, challenged, auth.as_mut()) {
+                let offered = headers.iter().filter_map(|(name, value)| {
+                    name.eq_ignore_ascii_case("www-authenticate")
+                        .then_some(value.as_str())
+                });
+                if auth.accept_challenge(offered) {
+                    challenged = true;
+                    continue;
+                }
+            }
+            return Ok(RawHttpResponse { status, body });
         }
-        buf
     };
+    tokio::time::timeout(Duration::from_secs(timeout_secs), exchange)
+        .await
+        .map_err(|_| format!("Request timed out after {}s", timeout_secs))?
+}
 
-    let split = full_response
+/// Status, headers and body of a raw HTTP/1.1 response.
+fn split_response(raw: &[u8]) -> (u16, Vec<(String, String)>, String) {
+    let split = raw
         .windows(4)
         .position(|w| w == b"\r\n\r\n")
-        .unwrap_or(full_response.len());
-    let header_buf = &full_response[..split];
-    let body_buf = if split + 4 <= full_response.len() {
-        &full_response[split + 4..]
-    } else {
-        &[]
-    };
+        .unwrap_or(raw.len());
+    let head = String::from_utf8_lossy(&raw[..split]);
+    let body = raw.get(split + 4..).unwrap_or(&[]);
 
-    let status = String::from_utf8_lossy(header_buf)
-        .lines()
+    let mut lines = head.lines();
+    let status = lines
         .next()
         .unwrap_or("")
         .split_whitespace()
         .nth(1)
         .and_then(|s| s.parse().ok())
         .unwrap_or(0u16);
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
 
-    Ok(RawHttpResponse {
-        status,
-        body: String::from_utf8_lossy(body_buf).into_owned(),
-    })
+    (status, headers, String::from_utf8_lossy(body).into_owned())
 }
 
-#[cfg(not(target_os = "windows"))]
-fn raw_tls_exchange(
-    tcp: TcpStream,
-    host: &str,
-    request: &str,
-    accept_invalid: bool,
-) -> Result<Vec<u8>, String> {
-    use std::ffi::CString;
-    use std::os::fd::FromRawFd;
-    use std::os::raw::{c_int, c_long, c_void};
-    use std::os::unix::io::IntoRawFd;
-
-    const SSL_CTRL_SET_TLSEXT_HOSTNAME: c_int = 55;
-    const SSL_CTRL_MODE: c_int = 33;
-    const SSL_MODE_RELEASE_BUFFERS: c_long = 0x00000010;
-
-    extern "C" {
-        fn TLS_client_method() -> *const c_void;
-        fn SSL_CTX_new(method: *const c_void) -> *mut c_void;
-        fn SSL_CTX_free(ctx: *mut c_void);
-        fn SSL_CTX_ctrl(ctx: *mut c_void, cmd: c_int, larg: c_long, parg: *mut c_void) -> c_long;
-        fn SSL_new(ctx: *mut c_void) -> *mut c_void;
-        fn SSL_free(ssl: *mut c_void);
-        fn SSL_set_fd(ssl: *mut c_void, fd: c_int) -> c_int;
-        fn SSL_connect(ssl: *mut c_void) -> c_int;
-        fn SSL_read(ssl: *mut c_void, buf: *mut c_void, num: c_int) -> c_int;
-        fn SSL_write(ssl: *mut c_void, buf: *const c_void, num: c_int) -> c_int;
-        fn SSL_ctrl(ssl: *mut c_void, cmd: c_int, larg: c_long, parg: *mut c_void) -> c_long;
-        fn SSL_CTX_set_verify(ctx: *mut c_void, mode: c_int, cb: *const c_void);
-        fn SSL_CTX_set_default_verify_paths(ctx: *mut c_void) -> c_int;
-        fn SSL_get_error(ssl: *const c_void, ret: c_int) -> c_int;
+/// Drivers with their own TLS stack cannot honour every option; refuse rather
+/// than connect with weaker settings than configured.
+#[cfg(any(feature = "kafka", feature = "mysql", feature = "mongodb"))]
+fn check_tls_support(
+    service: &str,
+    tls: &TlsClientConfig,
+    server_name: bool,
+    min_protocol_version: bool,
+) -> Result<(), String> {
+    if tls.client_cert.is_some() != tls.client_key.is_some() {
+        return Err("client_cert and client_key must be set together".to_string());
     }
+    if tls.server_name.is_some() && !server_name {
+        return Err(format!("{} checks do not support tls.server_name", service));
+    }
+    if tls.min_protocol_version.is_some() && !min_protocol_version {
+        return Err(format!(
+            "{} checks do not support tls.min_protocol_version",
+            service
+        ));
+    }
+    Ok(())
+}
 
-    unsafe {
-        let method = TLS_client_method();
-        if method.is_null() {
-            return Err("TLS_client_method failed".into());
-        }
-        let ctx = SSL_CTX_new(method);
-        if ctx.is_null() {
-            return Err("SSL_CTX_new failed".into());
-        }
-        SSL_CTX_set_default_verify_paths(ctx);
-        SSL_CTX_set_verify(ctx, if accept_invalid { 0 } else { 1 }, std::ptr::null());
-        SSL_CTX_ctrl(
-            ctx,
-            SSL_CTRL_MODE,
-            SSL_MODE_RELEASE_BUFFERS,
-            std::ptr::null_mut(),
-        );
-
-        let ssl = SSL_new(ctx);
-        if ssl.is_null() {
-            SSL_CTX_free(ctx);
-            return Err("SSL_new failed".into());
-        }
-
-        let chost = CString::new(host).map_err(|e| e.to_string())?;
-        SSL_ctrl(
-            ssl,
-            SSL_CTRL_SET_TLSEXT_HOSTNAME,
-            0,
-            chost.as_ptr() as *mut c_void,
-        );
-
-        let fd = tcp.into_raw_fd();
-        SSL_set_fd(ssl, fd);
-
-        let ret = SSL_connect(ssl);
-        if ret != 1 {
-            let err = SSL_get_error(ssl, ret);
-            SSL_free(ssl);
-            SSL_CTX_free(ctx);
-            drop(std::os::unix::io::OwnedFd::from_raw_fd(fd));
-            return Err(format!("SSL_connect failed: {}", err));
-        }
+// ─── PostgreSQL ───────────────────────────────────────────────────────────────
 
-        let data = request.as_bytes();
-        let mut written = 0;
-        while written < data.len() {
-            let r = SSL_write(
-                ssl,
-                data[written..].as_ptr() as *const c_void,
-                (data.len() - written) as c_int,
-            );
-            if r <= 0 {
-                SSL_free(ssl);
-                SSL_CTX_free(ctx);
-                return Err("SSL_write failed".into());
-            }
-            written += r as usize;
-        }
+/// TLS for tokio-postgres from the shared client configuration, so the CA
+/// bundle, client certificate, SNI override, minimum version and certificate
+/// verification apply.
+#[cfg(feature = "postgres")]
+struct PostgresTls(TlsClientConfig);
 
-        let mut buf = Vec::with_capacity(32 * 1024);
-        let mut tmp = [0u8; 4096];
-        loop {
-            let r = SSL_read(ssl, tmp.as_mut_ptr() as *mut c_void, tmp.len() as c_int);
-            if r <= 0 {
-                break;
-            }
-            buf.extend_from_slice(&tmp[..r as usize]);
+#[cfg(feature = "postgres")]
+impl<S> tokio_postgres::tls::MakeTlsConnect<S> for PostgresTls
+where
+    S: tokio::io::AsyncRead
+        + tokio::io::AsyncWrite
+        + Unpin
+        + std::fmt::Debug
+        + Send
+        + Sync
+        + 'static,
+{
+    type Stream = postgres_openssl::TlsStream<S>;
+    type TlsConnect = postgres_openssl::TlsConnector;
+    type Error = String;
+
+    fn make_tls_connect(&mut self, domain: &str) -> Result<Self::TlsConnect, String> {
+        let mut ssl = client_configuration(&self.0)?;
+        if self.0.verify_certificate {
+            // tokio-postgres drives the handshake, so OpenSSL has to refuse
+            // an untrusted server itself before the password is sent
+            ssl.set_verify(SslVerifyMode::PEER);
+            ssl.set_verify_hostname(true);
         }
-
-        SSL_free(ssl);
-        SSL_CTX_free(ctx);
-        let _tcp_back: TcpStream = std::os::unix::io::FromRawFd::from_raw_fd(fd);
-        Ok(buf)
+        Ok(postgres_openssl::TlsConnector::new(
+            ssl,
+            server_name(domain, &self.0),
+        ))
     }
 }
 
-#[cfg(target_os = "windows")]
-fn raw_tls_exchange(
-    _tcp: TcpStream,
-    _host: &str,
-    _request: &str,
-    _accept_invalid: bool,
-) -> Result<Vec<u8>, String> {
-    Err("HTTPS on Windows requires native-tls".into())
-}
-
-// ─── PostgreSQL ───────────────────────────────────────────────────────────────
-
 #[cfg(feature = "postgres")]
 pub async fn check_postgres(address: &str, config: &PostgresCheck) -> ServiceCheckResult {
     let start_time = Instant::now();
@@ -239,13 +222,21 @@ pub async fn check_postgres(address: &str, config: &PostgresCheck) -> ServiceChe
     };
     let connection_string = format!(
         "host={} port={} user={} password={} dbname={} sslmode={}",
-        address, config.port, config.username, config.password, config.database, ssl_mode
+        address,
+        config.port,
+        config.username,
+        config.password.expose(),
+        config.database,
+        ssl_mode
     );
 
     use tokio::time::timeout;
     match timeout(
         Duration::from_secs(config.timeout_seconds),
-        tokio_postgres::connect(&connection_string, tokio_postgres::NoTls),
+        tokio_postgres::connect(
+            &connection_string,
+            PostgresTls(config.tls.clone().unwrap_or_default()),
+        ),
     )
     .await
     {
@@ -302,7 +293,7 @@ pub async fn check_redis(address: &str, config: &RedisCheck) -> ServiceCheckResu
         redis: redis::RedisConnectionInfo {
             db: config.database as i64,
             username: None,
-            password: config.password.clone(),
+            password: config.password.as_ref().map(|p| p.expose().to_string()),
             protocol: redis::ProtocolVersion::RESP2,
         },
     };
@@ -351,37 +342,29 @@ pub async fn check_rabbitmq(address: &str, config: &RabbitMQCheck) -> ServiceChe
     let start_time = Instant::now();
     let management_port = config.port + 10000;
 
-    let addr = address.to_string();
-    let username = config.username.clone();
-    let password = config.password.clone();
-    let use_ssl = config.use_ssl;
-    let timeout_secs = config.timeout_seconds;
-
-    let result = tokio::task::spawn_blocking(move || {
-        raw_http_get(
-            &addr,
-            management_port,
-            "/api/overview",
-            use_ssl,
-            true,
-            Some((&username, &password)),
-            timeout_secs,
-        )
-    })
+    let tls = config
+        .use_ssl
+        .then(|| config.tls.clone().unwrap_or_default());
+
+    let result = raw_http_get(
+        address,
+        management_port,
+        "/api/overview",
+        tls.as_ref(),
+        config.proxy.as_ref(),
+        Some(&AuthConfig::Basic {
+            username: config.username.clone(),
+            password: config.password.clone(),
+        }),
+        config.timeout_seconds,
+    )
     .await;
 
     let response = match result {
-        Ok(Ok(r)) => r,
-        Ok(Err(e)) => {
-            return ServiceCheckResult {
-                status: CheckStatus::Unhealthy(format!("RabbitMQ request failed: {}", e)),
-                response_time_ms: start_time.elapsed().as_millis(),
-                service_info: None,
-            }
-        }
+        Ok(r) => r,
         Err(e) => {
             return ServiceCheckResult {
-                status: CheckStatus::Unhealthy(format!("RabbitMQ task failed: {}", e)),
+                status: CheckStatus::Unhealthy(format!("RabbitMQ request failed: {}", e)),
                 response_time_ms: start_time.elapsed().as_millis(),
                 service_info: None,
             }
@@ -433,6 +416,24 @@ pub async fn check_kafka(address: &str, config: &KafkaCheck) -> ServiceCheckResu
     );
     if config.use_ssl {
         client_config.set("security.protocol", "SSL");
+        let tls = config.tls.clone().unwrap_or_default();
+        if let Err(e) = check_tls_support("Kafka", &tls, false, false) {
+            return ServiceCheckResult {
+                status: CheckStatus::Unhealthy(e),
+                response_time_ms: start_time.elapsed().as_millis(),
+                service_info: None,
+            };
+        }
+        if let Some(ca_file) = &tls.ca_file {
+            client_config.set("ssl.ca.location", ca_file);
+        }
+        if let (Some(cert), Some(key)) = (&tls.client_cert, &tls.client_key) {
+            client_config.set("ssl.certificate.location", cert);
+            client_config.set("ssl.key.location", key);
+        }
+        if !tls.verify_certificate {
+            client_config.set("enable.ssl.certificate.verification", "false");
+        }
     }
 
     use tokio::time::timeout;
@@ -473,17 +474,39 @@ use mysql_async::prelude::*;
 pub async fn check_mysql(address: &str, config: &MySQLCheck) -> ServiceCheckResult {
     let start_time = Instant::now();
 
+    let ssl_opts = if config.use_ssl {
+        let tls = config.tls.clone().unwrap_or_default();
+        if let Err(e) = check_tls_support("MySQL", &tls, false, false) {
+            return ServiceCheckResult {
+                status: CheckStatus::Unhealthy(e),
+                response_time_ms: start_time.elapsed().as_millis(),
+                service_info: None,
+            };
+        }
+        let identity = match (tls.client_cert, tls.client_key) {
+            (Some(cert), Some(key)) => Some(mysql_async::ClientIdentity::new(
+                std::path::PathBuf::from(cert),
+                std::path::PathBuf::from(key),
+            )),
+            _ => None,
+        };
+        Some(
+            mysql_async::SslOpts::default()
+                .with_root_cert_path(tls.ca_file.map(std::path::PathBuf::from))
+                .with_client_identity(identity)
+                .with_danger_accept_invalid_certs(!tls.verify_certificate)
+                .with_danger_skip_domain_validation(!tls.verify_certificate),
+        )
+    } else {
+        None
+    };
     let opts = mysql_async::OptsBuilder::default()
         .ip_or_hostname(address)
         .tcp_port(config.port)
         .user(Some(&config.username))
-        .pass(Some(&config.password))
+        .pass(Some(config.password.expose()))
         .db_name(Some(&config.database))
-        .ssl_opts(if config.use_ssl {
-            Some(mysql_async::SslOpts::default())
-        } else {
-            None
-        });
+        .ssl_opts(ssl_opts);
 
     use tokio::time::timeout;
     match timeout(
@@ -539,14 +562,52 @@ pub async fn check_mongodb(address: &str, config: &MongoDBCheck) -> ServiceCheck
         if config.use_ssl { "?ssl=true" } else { "" }
     );
     if let (Some(u), Some(p)) = (&config.username, &config.password) {
-        uri = uri.replace("mongodb://", &format!("mongodb://{}:{}@", u, p));
+        uri = uri.replace(
+            "mongodb://",
+            &format!(
+                "mongodb://{}:{}@",
+                urlencoding::encode(u),
+                urlencoding::encode(p.expose())
+            ),
+        );
     }
 
+    let tls = match (&config.tls, config.use_ssl) {
+        (Some(tls), true) => {
+            // The driver reads the certificate and its key from one PEM file
+            let combined = match (&tls.client_cert, &tls.client_key) {
+                (Some(cert), Some(key)) if cert != key => Err(
+                    "MongoDB checks need client_cert and client_key in the same PEM file"
+                        .to_string(),
+                ),
+                _ => check_tls_support("MongoDB", tls, false, false),
+            };
+            if let Err(e) = combined {
+                return ServiceCheckResult {
+                    status: CheckStatus::Unhealthy(e),
+                    response_time_ms: start_time.elapsed().as_millis(),
+                    service_info: None,
+                };
+            }
+            Some(
+                mongodb::options::TlsOptions::builder()
+                    .ca_file_path(tls.ca_file.clone().map(std::path::PathBuf::from))
+                    .cert_key_file_path(tls.client_cert.clone().map(std::path::PathBuf::from))
+                    .allow_invalid_certificates(!tls.verify_certificate)
+                    .build(),
+            )
+        }
+        _ => None,
+    };
+
     use tokio::time::timeout;
-    match timeout(
-        Duration::from_secs(config.timeout_seconds),
-        mongodb::Client::with_uri_str(&uri),
-    )
+    match timeout(Duration::from_secs(config.timeout_seconds), async {
+        let mut options = mongodb::options::ClientOptions::parse(&uri).await?;
+        if let Some(tls) = tls {
+            options.tls = Some(mongodb::options::Tls::Enabled(tls));
+        }
+        mongodb::Client::with_options(options)
+    })
     .await
     {
         Ok(Ok(client)) => {
@@ -598,50 +659,34 @@ pub async fn check_mongodb(address: &str, config: &MongoDBCheck) -> ServiceCheck
 pub async fn check_elasticsearch(address: &str, config: &ElasticsearchCheck) -> ServiceCheckResult {
     let start_time = Instant::now();
 
-    let addr = address.to_string();
-    let port = config.port;
-    let use_ssl = config.use_ssl;
-    let username = config.username.clone();
-    let password = config.password.clone();
-    let timeout_secs = config.timeout_seconds;
-
-    let result = tokio::task::spawn_blocking(move || {
-        // Build auth tuple from owned strings so lifetimes work in the closure
-        match (&username, &password) {
-            (Some(u), Some(p)) => raw_http_get(
-                &addr,
-                port,
-                "/_cluster/health",
-                use_ssl,
-                true,
-                Some((u.as_str(), p.as_str())),
-                timeout_secs,
-            ),
-            _ => raw_http_get(
-                &addr,
-                port,
-                "/_cluster/health",
-                use_ssl,
-                true,
-                None,
-                timeout_secs,
-            ),
-        }
-    })
+    let tls = config
+        .use_ssl
+        .then(|| config.tls.clone().unwrap_or_default());
+    let auth = match (&config.auth, &config.username, &config.password) {
+        (Some(auth), _, _) => Some(auth.clone()),
+        (None, Some(username), Some(password)) => Some(AuthConfig::Basic {
+            username: username.clone(),
+            password: password.clone(),
+        }),
+        _ => None,
+    };
+
+    let result = raw_http_get(
+        address,
+        config.port,
+        "/_cluster/health",
+        tls.as_ref(),
+        config.proxy.as_ref(),
+        auth.as_ref(),
+        config.timeout_seconds,
+    )
     .await;
 
     let response = match result {
-        Ok(Ok(r)) => r,
-        Ok(Err(e)) => {
-            return ServiceCheckResult {
-                status: CheckStatus::Unhealthy(format!("Elasticsearch request failed: {}", e)),
-                response_time_ms: start_time.elapsed().as_millis(),
-                service_info: None,
-            }
-        }
+        Ok(r) => r,
         Err(e) => {
             return ServiceCheckResult {
-                status: CheckStatus::Unhealthy(format!("Elasticsearch task failed: {}", e)),
+                status: CheckStatus::Unhealthy(format!("Elasticsearch request failed: {}", e)),
                 response_time_ms: start_time.elapsed().as_millis(),
                 service_info: None,
             }
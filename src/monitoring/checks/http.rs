@@ -1,181 +1,107 @@
-use std::io::{Read, Write};
-use std::net::{TcpStream, ToSocketAddrs};
-use std::time::{Duration, Instant};
-use log::{info, warn};
-use crate::config::{HttpCheck, HttpProtocol, HttpMethod as ConfigHttpMethod, AuthConfig};
-use crate::monitoring::types::{HttpTargetCheckResult, CheckStatus};
+use crate::config::{
+    AuthConfig, HttpCheck, HttpMethod as ConfigHttpMethod, HttpProtocol, RedirectPolicy,
+};
 use crate::monitoring::assertions::evaluate_assertions_with_data;
-use crate::monitoring::auth::get_oauth2_token;
+use crate::monitoring::auth::{invalidate_oauth2_token, RequestAuth};
+use crate::monitoring::http_client::{build_client, ClientOptions, ConnectionInfo, HttpClient};
+use crate::monitoring::net;
+use crate::monitoring::proxy;
+use crate::monitoring::tls::TlsCertInfo;
+use crate::monitoring::types::{
+    AddressCheckResult, CheckStatus, HttpPhaseTimings, HttpTargetCheckResult, RedirectHop,
+};
+use hyper::body::HttpBody;
+use log::info;
+use std::collections::HashMap;
+use std::io::Read;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
-// ─── Low-level TLS (Unix only) ──────────────────────────────────────────────
+// ─── Shared client ────────────────────────────────────────────────────────────
 //
-// All opaque OpenSSL types are represented as *mut/*const c_void so the extern
-// "C" signatures are identical to those in auth.rs and database.rs.  This
-// avoids the clashing_extern_declarations warnings that arise when the same
-// symbol is declared with different (but ABI-compatible) pointer types.
-
-#[cfg(not(target_os = "windows"))]
-mod tls_ffi {
-    use std::ffi::CString;
-    use std::net::TcpStream;
-    use std::os::fd::FromRawFd;
-    use std::os::raw::{c_int, c_long, c_void};
-    use std::os::unix::io::IntoRawFd;
-
-    pub const SSL_MODE_RELEASE_BUFFERS: c_long = 0x00000010;
-    pub const SSL_CTRL_SET_TLSEXT_HOSTNAME: c_int = 55;
-    pub const SSL_CTRL_MODE: c_int = 33;
-
-    // All pointer types use c_void — same as auth.rs — to avoid duplicate
-    // extern symbol warnings.
-    #[link(name = "ssl")]
-    #[link(name = "crypto")]
-    extern "C" {
-        pub fn TLS_client_method() -> *const c_void;
-        pub fn SSL_CTX_new(method: *const c_void) -> *mut c_void;
-        pub fn SSL_CTX_free(ctx: *mut c_void);
-        pub fn SSL_CTX_ctrl(ctx: *mut c_void, cmd: c_int, larg: c_long, parg: *mut c_void) -> c_long;
-        pub fn SSL_new(ctx: *mut c_void) -> *mut c_void;
-        pub fn SSL_free(ssl: *mut c_void);
-        pub fn SSL_set_fd(ssl: *mut c_void, fd: c_int) -> c_int;
-        pub fn SSL_connect(ssl: *mut c_void) -> c_int;
-        pub fn SSL_read(ssl: *mut c_void, buf: *mut c_void, num: c_int) -> c_int;
-        pub fn SSL_write(ssl: *mut c_void, buf: *const c_void, num: c_int) -> c_int;
-        pub fn SSL_ctrl(ssl: *mut c_void, cmd: c_int, larg: c_long, parg: *mut c_void) -> c_long;
-        pub fn SSL_CTX_set_verify(ctx: *mut c_void, mode: c_int, cb: *const c_void);
-        pub fn SSL_CTX_set_default_verify_paths(ctx: *mut c_void) -> c_int;
-        pub fn SSL_get_error(ssl: *const c_void, ret: c_int) -> c_int;
-
-        // Certificate access — OpenSSL 3.x names (borrowed refs, no free needed).
-        // SSL_get0_peer_certificate returns a borrowed X509*; caller must NOT free it.
-        pub fn SSL_get0_peer_certificate(ssl: *const c_void) -> *const c_void;
-        // X509_get0_notAfter returns a borrowed ASN1_TIME*; caller must NOT free it.
-        pub fn X509_get0_notAfter(cert: *const c_void) -> *const c_void;
-        // ASN1_TIME_diff: diff = to - from, result in days+seconds.
-        pub fn ASN1_TIME_diff(
-            pday: *mut c_int,
-            psec: *mut c_int,
-            from: *const c_void,
-            to: *const c_void,
-        ) -> c_int;
-        // X509_gmtime_adj(NULL, 0) returns a newly-allocated ASN1_TIME for "now".
-        pub fn X509_gmtime_adj(s: *mut c_void, adj: c_long) -> *mut c_void;
-        pub fn ASN1_TIME_free(t: *mut c_void);
-    }
-
-    pub struct TlsStream {
-        pub ssl: *mut c_void,
-        pub ctx: *mut c_void,
-        _tcp: std::mem::ManuallyDrop<TcpStream>,
-    }
-
-    impl TlsStream {
-        pub fn new(tcp: TcpStream, hostname: &str, verify_cert: bool) -> Result<Self, String> {
-            unsafe {
-                let method = TLS_client_method();
-                if method.is_null() { return Err("TLS_client_method failed".into()); }
-
-                let ctx = SSL_CTX_new(method);
-                if ctx.is_null() { return Err("SSL_CTX_new failed".into()); }
-
-                SSL_CTX_set_default_verify_paths(ctx);
-                SSL_CTX_set_verify(ctx, if verify_cert { 1 } else { 0 }, std::ptr::null());
-                SSL_CTX_ctrl(ctx, SSL_CTRL_MODE, SSL_MODE_RELEASE_BUFFERS, std::ptr::null_mut());
-
-                let ssl = SSL_new(ctx);
-                if ssl.is_null() {
-                    SSL_CTX_free(ctx);
-                    return Err("SSL_new failed".into());
-                }
-
-                let chost = CString::new(hostname).map_err(|e| e.to_string())?;
-                SSL_ctrl(ssl, SSL_CTRL_SET_TLSEXT_HOSTNAME, 0, chost.as_ptr() as *mut c_void);
+// Runs of the same HTTP check share one client and with it the keep-alive
+// connection pool, so repeated runs against a target reuse their connections
+// instead of paying for DNS, TCP and TLS setup every interval. Clients are
+// keyed by target, and ones left unused (a removed check, an address that
+// dropped out of DNS) are expired so the cache cannot grow without bound.
+
+/// A client unused for this long is dropped along with its pool.
+const CLIENT_IDLE_EXPIRY: Duration = Duration::from_secs(10 * 60);
+/// Beyond this many clients the least recently used one is dropped.
+const MAX_CLIENTS: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ClientKey {
+    target: String, // host:port of the check
+    options: ClientOptions,
+}
 
-                let fd = tcp.into_raw_fd();
-                SSL_set_fd(ssl, fd);
+struct CachedClient {
+    client: HttpClient,
+    last_used: Instant,
+}
 
-                let ret = SSL_connect(ssl);
-                if ret != 1 {
-                    let err = SSL_get_error(ssl, ret);
-                    SSL_free(ssl);
-                    SSL_CTX_free(ctx);
-                    drop(std::os::unix::io::OwnedFd::from_raw_fd(fd));
-                    return Err(format!("SSL_connect failed, error code: {}", err));
-                }
+#[derive(Default)]
+struct ClientCache {
+    clients: HashMap<ClientKey, CachedClient>,
+}
 
-                let tcp_back: TcpStream = std::os::unix::io::FromRawFd::from_raw_fd(fd);
-                Ok(TlsStream { ssl, ctx, _tcp: std::mem::ManuallyDrop::new(tcp_back) })
+impl ClientCache {
+    fn get(&mut self, key: ClientKey, now: Instant) -> HttpClient {
+        self.clients.retain(|_, cached| {
+            now.saturating_duration_since(cached.last_used) < CLIENT_IDLE_EXPIRY
+        });
+        if !self.clients.contains_key(&key) && self.clients.len() >= MAX_CLIENTS {
+            let oldest = self
+                .clients
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.clients.remove(&oldest);
             }
         }
+        let cached = self
+            .clients
+            .entry(key)
+            .or_insert_with_key(|key| CachedClient {
+                client: build_client(key.options.clone()),
+                last_used: now,
+            });
+        cached.last_used = now;
+        cached.client.clone()
+    }
+}
 
-        /// Returns the number of days until cert expiry (negative = already expired).
-        /// Uses OpenSSL 3.x borrowed-reference API (no free required).
-        pub fn cert_expiry_days(&self) -> Option<i64> {
-            unsafe {
-                // Borrowed ref — do NOT call X509_free on it.
-                let cert = SSL_get0_peer_certificate(self.ssl);
-                if cert.is_null() { return None; }
-
-                // Borrowed ref — do NOT call ASN1_TIME_free on it.
-                let not_after = X509_get0_notAfter(cert);
-                if not_after.is_null() { return None; }
-
-                // Allocate an ASN1_TIME for "now" — this one we must free.
-                let now = X509_gmtime_adj(std::ptr::null_mut(), 0);
-                if now.is_null() { return None; }
-
-                let mut pday: std::os::raw::c_int = 0;
-                let mut psec: std::os::raw::c_int = 0;
-                // diff = not_after − now
-                let ok = ASN1_TIME_diff(&mut pday, &mut psec, now, not_after);
-                ASN1_TIME_free(now);
-
-                if ok == 0 { None } else { Some(pday as i64) }
-            }
-        }
+static HTTP_CLIENTS: OnceLock<Mutex<ClientCache>> = OnceLock::new();
 
-        pub fn write_all(&mut self, data: &[u8]) -> Result<(), String> {
-            let mut written = 0;
-            while written < data.len() {
-                let ret = unsafe {
-                    SSL_write(
-                        self.ssl,
-                        data[written..].as_ptr() as *const c_void,
-                        (data.len() - written) as std::os::raw::c_int,
-                    )
-                };
-                if ret <= 0 { return Err(format!("SSL_write failed: {}", ret)); }
-                written += ret as usize;
-            }
-            Ok(())
-        }
+fn http_client(address: &str, port: u16, options: ClientOptions) -> HttpClient {
+    let key = ClientKey {
+        target: format!("{}:{}", address, port),
+        options,
+    };
+    let clients = HTTP_CLIENTS.get_or_init(|| Mutex::new(ClientCache::default()));
+    clients
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(key, Instant::now())
+}
 
-        pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, String> {
-            let ret = unsafe {
-                SSL_read(
-                    self.ssl,
-                    buf.as_mut_ptr() as *mut c_void,
-                    buf.len() as std::os::raw::c_int,
-                )
-            };
-            if ret < 0 { Err(format!("SSL_read failed: {}", ret)) } else { Ok(ret as usize) }
-        }
-    }
-
-    impl Drop for TlsStream {
-        fn drop(&mut self) {
-            unsafe {
-                SSL_free(self.ssl);
-                SSL_CTX_free(self.ctx);
-                std::mem::ManuallyDrop::drop(&mut self._tcp);
-            }
-        }
+fn client_options(config: &HttpCheck, pinned: Option<(String, IpAddr)>) -> ClientOptions {
+    let tls = config.tls.clone().unwrap_or_default();
+    ClientOptions {
+        family: config.address_family,
+        pinned,
+        verify: config.check_ssl_certificate && tls.verify_certificate,
+        tls,
+        proxy: config.proxy.clone(),
     }
 }
 
 // ─── Response helpers ────────────────────────────────────────────────────────
 
-fn parse_status_code(header_buf: &[u8]) -> u16 {
+pub(crate) fn parse_status_code(header_buf: &[u8]) -> u16 {
     String::from_utf8_lossy(header_buf)
         .lines()
         .next()
@@ -186,13 +112,15 @@ fn parse_status_code(header_buf: &[u8]) -> u16 {
         .unwrap_or(0)
 }
 
-fn parse_headers(header_buf: &[u8]) -> http::HeaderMap {
+pub(crate) fn parse_headers(header_buf: &[u8]) -> http::HeaderMap {
     let mut map = http::HeaderMap::new();
     let text = String::from_utf8_lossy(header_buf);
     for line in text.lines().skip(1) {
-        if line.is_empty() { break; }
+        if line.is_empty() {
+            break;
+        }
         if let Some(colon) = line.find(':') {
-            let name  = line[..colon].trim();
+            let name = line[..colon].trim();
             let value = line[colon + 1..].trim();
             if let (Ok(hn), Ok(hv)) = (
                 http::header::HeaderName::from_bytes(name.as_bytes()),
@@ -205,151 +133,397 @@ fn parse_headers(header_buf: &[u8]) -> http::HeaderMap {
     map
 }
 
-// ─── Raw HTTP/HTTPS request ───────────────────────────────────────────────────
+/// hyper 0.14 is built on `http` 0.2; assertions use the crate's `http` 1.x types.
+fn convert_headers(headers: &hyper::HeaderMap) -> http::HeaderMap {
+    let mut map = http::HeaderMap::with_capacity(headers.len());
+    for (name, value) in headers {
+        if let (Ok(hn), Ok(hv)) = (
+            http::header::HeaderName::from_bytes(name.as_str().as_bytes()),
+            http::header::HeaderValue::from_bytes(value.as_bytes()),
+        ) {
+            map.append(hn, hv);
+        }
+    }
+    map
+}
+
+/// Flatten an error and its sources; the top-level message alone
+/// ("error trying to connect") hides the actual cause.
+fn describe_error(err: &(dyn std::error::Error + 'static)) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        message.push_str(&format!(": {}", cause));
+        source = cause.source();
+    }
+    message
+}
 
-struct RawResponse {
+/// Run `future` unless `deadline` passes first.
+async fn before_deadline<T>(
+    deadline: Instant,
+    timeout: Duration,
+    future: impl std::future::Future<Output = Result<T, String>>,
+) -> Result<T, String> {
+    tokio::time::timeout_at(deadline.into(), future)
+        .await
+        .unwrap_or_else(|_| Err(format!("timed out after {}s", timeout.as_secs())))
+}
+
+// ─── HTTP/HTTPS request ───────────────────────────────────────────────────────
+
+struct HttpResponse {
     status_code: u16,
     headers: http::HeaderMap,
     body: String,
+    certificate: Option<TlsCertInfo>,
+    redirects: Vec<RedirectHop>,
+    timings: HttpPhaseTimings,
 }
 
-fn do_http_request(
-    address: &str,
-    port: u16,
-    path: &str,
-    method: &str,
-    extra_headers: &[(String, String)],
-    timeout: Duration,
-    is_https: bool,
-    accept_invalid_certs: bool,
-) -> Result<RawResponse, String> {
-    let addr_str = format!("{}:{}", address, port);
-    let addrs: Vec<_> = addr_str
-        .to_socket_addrs()
-        .map_err(|e| format!("DNS lookup failed: {}", e))?
-        .collect();
-    if addrs.is_empty() {
-        return Err("No addresses resolved".into());
-    }
-
-    let tcp = TcpStream::connect_timeout(&addrs[0], timeout)
-        .map_err(|e| format!("TCP connect failed: {}", e))?;
-    tcp.set_read_timeout(Some(timeout))
-        .map_err(|e| format!("set_read_timeout failed: {}", e))?;
-    tcp.set_nodelay(true).ok();
+fn is_redirect(status: hyper::StatusCode) -> bool {
+    matches!(status.as_u16(), 301 | 302 | 303 | 307 | 308)
+}
 
-    let mut req = format!(
-        "{} {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: ruptime/1.0\r\nAccept: */*\r\nConnection: close\r\n",
-        method, path, address
-    );
-    for (k, v) in extra_headers {
-        req.push_str(&format!("{}: {}\r\n", k, v));
+/// Phases of one request. DNS, connect and TLS are zero when a pooled
+/// connection was reused; TTFB is the rest of the wait for the headers.
+fn phase_timings(
+    info: Option<&ConnectionInfo>,
+    request_start: Instant,
+    headers_received: Duration,
+    download: Duration,
+) -> HttpPhaseTimings {
+    let reused = info.is_none_or(|info| info.established_at < request_start);
+    let (dns, connect, tls) = match info {
+        Some(info) if !reused => (info.dns, info.connect, info.tls),
+        Some(info) => (
+            Duration::ZERO,
+            Duration::ZERO,
+            info.tls.map(|_| Duration::ZERO),
+        ),
+        None => (Duration::ZERO, Duration::ZERO, None),
+    };
+    HttpPhaseTimings {
+        dns_ms: dns.as_millis(),
+        connect_ms: connect.as_millis(),
+        tls_ms: tls.map(|t| t.as_millis()),
+        ttfb_ms: headers_received
+            .saturating_sub(dns + connect + tls.unwrap_or_default())
+            .as_millis(),
+        download_ms: download.as_millis(),
+        connection_reused: reused,
     }
-    req.push_str("\r\n");
+}
 
-    let full_response: Vec<u8> = if is_https {
-        #[cfg(not(target_os = "windows"))]
+/// Send the request and, when enabled, follow redirects by hand so every hop
+/// can be timed and recorded. The timeout covers the whole chain.
+async fn send_request(
+    client: &HttpClient,
+    config: &HttpCheck,
+    url: &str,
+    extra_headers: &[(String, String)],
+    mut auth: Option<&mut RequestAuth<'_>>,
+) -> Result<HttpResponse, String> {
+    let timeout = Duration::from_secs(config.timeout_seconds);
+    let deadline = Instant::now() + timeout;
+    let mut current = url::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    if let Some(auth) = auth.as_deref() {
+        auth.apply_to_url(&mut current);
+    }
+    let origin = current.origin();
+    let origin_host = current.host_str().map(str::to_string);
+    let mut method = to_hyper_method(&config.method)?;
+    let body = encode_body(config).await?;
+    let mut send_body = true;
+    let mut redirects = Vec::new();
+    let mut challenged = false;
+
+    let (response, request_start, headers_received) = loop {
+        // Credentials and custom headers are meant for the configured origin only.
+        let cross_origin = current.origin() != origin;
+        let headers = if cross_origin { &[][..] } else { extra_headers };
+        let has_header = |name: &str| headers.iter().any(|(k, _)| k.eq_ignore_ascii_case(name));
+        let mut request = hyper::Request::builder()
+            .method(method.clone())
+            .uri(current.as_str());
+        if !has_header("user-agent") {
+            request = request.header(hyper::header::USER_AGENT, "ruptime/1.0");
+        }
+        if !has_header("accept-encoding") {
+            request = request.header(hyper::header::ACCEPT_ENCODING, "gzip, deflate, br");
+        }
+        for (k, v) in headers {
+            request = request.header(k.as_str(), v.as_str());
+        }
+        if let (false, Some(auth)) = (cross_origin, auth.as_deref_mut()) {
+            let signed = match (&body, send_body) {
+                (Some(body), true) => &body.bytes[..],
+                _ => &[],
+            };
+            for (k, v) in auth.headers(method.as_str(), &current, signed)? {
+                request = request.header(k, v);
+            }
+        }
+        // A forwarded request is read by the proxy; a tunnelled one is not
+        let is_https = current.scheme() == "https";
+        let port = current.port_or_known_default().unwrap_or(80);
+        if let Some(proxy) = proxy::select(
+            config.proxy.as_ref(),
+            current.host_str().unwrap_or(""),
+            port,
+        )? {
+            if let (true, Some(value)) = (proxy.forwards(is_https), proxy.authorization()) {
+                request = request.header(hyper::header::PROXY_AUTHORIZATION, value);
+            }
+        }
+        let request = match (&body, send_body) {
+            (Some(body), true) => {
+                if !has_header("content-type") {
+                    request = request.header(hyper::header::CONTENT_TYPE, body.content_type);
+                }
+                request.body(hyper::Body::from(body.bytes.clone()))
+            }
+            _ => request.body(hyper::Body::empty()),
+        }
+        .map_err(|e| format!("Invalid request: {}", e))?;
+
+        let hop_start = Instant::now();
+        let response = before_deadline(deadline, timeout, async {
+            client
+                .request(request)
+                .await
+                .map_err(|e| describe_error(&e))
+        })
+        .await?;
+        let status = response.status();
+        // A Digest challenge is answered by sending the same request again
+        if let (hyper::StatusCode::UNAUTHORIZED, false, false, Some(auth)) =
+            (status, challenged, cross_origin, auth.as_deref_mut())
         {
-            let mut tls = tls_ffi::TlsStream::new(tcp, address, !accept_invalid_certs)
-                .map_err(|e| format!("TLS handshake failed: {}", e))?;
-            tls.write_all(req.as_bytes())?;
-            let mut buf = Vec::with_capacity(32 * 1024);
-            let mut tmp = [0u8; 16 * 1024];
-            loop {
-                let n = tls.read(&mut tmp)?;
-                if n == 0 { break; }
-                buf.extend_from_slice(&tmp[..n]);
+            let offered = response.headers().get_all(hyper::header::WWW_AUTHENTICATE);
+            if auth.accept_challenge(offered.iter().filter_map(|v| v.to_str().ok())) {
+                challenged = true;
+                continue;
             }
-            buf
         }
-        #[cfg(target_os = "windows")]
-        return Err("HTTPS on Windows requires native-tls (not available in zero-dep mode)".into());
-    } else {
-        let mut tcp = tcp;
-        tcp.write_all(req.as_bytes())
-            .map_err(|e| format!("Write failed: {}", e))?;
-        let mut buf = Vec::with_capacity(32 * 1024);
-        let mut tmp = [0u8; 16 * 1024];
-        loop {
-            let n = tcp.read(&mut tmp).unwrap_or(0);
-            if n == 0 { break; }
-            buf.extend_from_slice(&tmp[..n]);
+        let location = response
+            .headers()
+            .get(hyper::header::LOCATION)
+            .and_then(|v| v.to_str().ok());
+        let (true, Some(location)) = (config.follow_redirects && is_redirect(status), location)
+        else {
+            break (response, hop_start, hop_start.elapsed());
+        };
+
+        let next = current
+            .join(location)
+            .map_err(|e| format!("Invalid redirect location '{}': {}", location, e))?;
+        redirects.push(RedirectHop {
+            url: current.to_string(),
+            status_code: status.as_u16(),
+            location: next.to_string(),
+            duration_ms: hop_start.elapsed().as_millis(),
+        });
+        info!("Redirect {} {} -> {}", status.as_u16(), current, next);
+
+        if redirects.len() > config.max_redirects {
+            return Err(format!(
+                "Too many redirects (limit {})",
+                config.max_redirects
+            ));
+        }
+        if config.redirect_policy == RedirectPolicy::SameHost
+            && next.host_str() != origin_host.as_deref()
+        {
+            return Err(format!(
+                "Redirect to {} leaves host {}",
+                next,
+                origin_host.unwrap_or_default()
+            ));
         }
-        buf
+        // 303 always, and 301/302 for POST by long-standing browser practice, switch to GET.
+        if (status == hyper::StatusCode::SEE_OTHER && method != hyper::Method::HEAD)
+            || (matches!(status.as_u16(), 301 | 302) && method == hyper::Method::POST)
+        {
+            method = hyper::Method::GET;
+            send_body = false;
+        }
+        challenged = false;
+        current = next;
     };
 
-    let split_pos = full_response
-        .windows(4)
-        .position(|w| w == b"\r\n\r\n")
-        .unwrap_or(full_response.len());
-
-    let header_buf = &full_response[..split_pos];
-    let body_buf   = if split_pos + 4 <= full_response.len() { &full_response[split_pos + 4..] } else { &[] };
+    let connection = response.extensions().get::<Arc<ConnectionInfo>>().cloned();
+    let status_code = response.status().as_u16();
+    let headers = convert_headers(response.headers());
+    let download_start = Instant::now();
+    let body = before_deadline(
+        deadline,
+        timeout,
+        read_body(response, config.max_body_bytes),
+    )
+    .await?;
+    let timings = phase_timings(
+        connection.as_deref(),
+        request_start,
+        headers_received,
+        download_start.elapsed(),
+    );
 
-    Ok(RawResponse {
-        status_code: parse_status_code(header_buf),
-        headers:     parse_headers(header_buf),
-        body:        String::from_utf8_lossy(body_buf).into_owned(),
+    Ok(HttpResponse {
+        status_code,
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+        certificate: connection.and_then(|info| info.certificate.clone()),
+        redirects,
+        timings,
     })
 }
 
-// ─── SSL certificate probe ────────────────────────────────────────────────────
+fn body_too_large(max_body_bytes: usize) -> String {
+    format!(
+        "Response body exceeds the limit of {} bytes",
+        max_body_bytes
+    )
+}
 
-#[cfg(not(target_os = "windows"))]
-fn probe_ssl_cert(address: &str, port: u16) -> (Option<i64>, Option<bool>) {
-    let addr_str = format!("{}:{}", address, port);
-    let addrs: Vec<_> = match addr_str.to_socket_addrs() {
-        Ok(a) => a.collect(),
-        Err(e) => {
-            warn!("DNS lookup failed for SSL cert probe on {}: {}", address, e);
-            return (None, Some(false));
+/// Read the body chunk by chunk; hyper takes care of Content-Length and
+/// chunked framing (trailers included). The limit applies to the raw body and
+/// again to the decoded one.
+async fn read_body(
+    mut response: hyper::Response<hyper::Body>,
+    max_body_bytes: usize,
+) -> Result<Vec<u8>, String> {
+    let body = response.body_mut();
+    if body.size_hint().lower() > max_body_bytes as u64 {
+        return Err(body_too_large(max_body_bytes));
+    }
+
+    let mut raw = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk =
+            chunk.map_err(|e| format!("Failed to read response body: {}", describe_error(&e)))?;
+        if raw.len() + chunk.len() > max_body_bytes {
+            return Err(body_too_large(max_body_bytes));
         }
-    };
-    if addrs.is_empty() { return (None, Some(false)); }
+        raw.extend_from_slice(&chunk);
+    }
 
-    let tcp = match TcpStream::connect_timeout(&addrs[0], Duration::from_secs(5)) {
-        Ok(s) => s,
-        Err(e) => {
-            warn!("TCP connect failed for SSL cert probe on {}:{}: {}", address, port, e);
-            return (None, Some(false));
+    let encoding = response
+        .headers()
+        .get(hyper::header::CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+    decode_body(&encoding, raw, max_body_bytes)
+}
+
+/// Undo the `Content-Encoding` negotiated through `Accept-Encoding`.
+fn decode_body(encoding: &str, raw: Vec<u8>, max_body_bytes: usize) -> Result<Vec<u8>, String> {
+    let read_limited = |reader: &mut dyn Read| -> Result<Vec<u8>, String> {
+        let mut decoded = Vec::new();
+        reader
+            .take(max_body_bytes as u64 + 1)
+            .read_to_end(&mut decoded)
+            .map_err(|e| format!("Failed to decode {} response body: {}", encoding, e))?;
+        if decoded.len() > max_body_bytes {
+            return Err(body_too_large(max_body_bytes));
         }
+        Ok(decoded)
     };
 
-    // verify_cert = false so we always get the cert even for self-signed
-    match tls_ffi::TlsStream::new(tcp, address, false) {
-        Ok(tls) => match tls.cert_expiry_days() {
-            Some(days) => {
-                info!("SSL cert for {}: {} days remaining, valid: {}", address, days, days > 0);
-                (Some(days), Some(days > 0))
-            }
-            None => {
-                warn!("Could not extract certificate from TLS stream for {}", address);
-                (None, Some(false))
-            }
-        },
-        Err(e) => {
-            warn!("TLS connection for cert probe failed for {}:{}: {}", address, port, e);
-            (None, Some(false))
+    match encoding {
+        "" | "identity" => Ok(raw),
+        "gzip" | "x-gzip" => read_limited(&mut flate2::read::MultiGzDecoder::new(&raw[..])),
+        // Some servers send raw deflate instead of the zlib wrapper RFC 9110 asks for.
+        "deflate" if raw.first().is_some_and(|b| b & 0x0f == 8) => {
+            read_limited(&mut flate2::read::ZlibDecoder::new(&raw[..]))
         }
+        "deflate" => read_limited(&mut flate2::read::DeflateDecoder::new(&raw[..])),
+        "br" => read_limited(&mut brotli::Decompressor::new(&raw[..], 4096)),
+        other => Err(format!("Unsupported Content-Encoding '{}'", other)),
     }
 }
 
-#[cfg(target_os = "windows")]
-fn probe_ssl_cert(_address: &str, _port: u16) -> (Option<i64>, Option<bool>) {
-    (None, None)
+// ─── HTTP method mapping ──────────────────────────────────────────────────────
+
+fn to_hyper_method(method: &ConfigHttpMethod) -> Result<hyper::Method, String> {
+    Ok(match method {
+        ConfigHttpMethod::Get => hyper::Method::GET,
+        ConfigHttpMethod::Post => hyper::Method::POST,
+        ConfigHttpMethod::Put => hyper::Method::PUT,
+        ConfigHttpMethod::Delete => hyper::Method::DELETE,
+        ConfigHttpMethod::Head => hyper::Method::HEAD,
+        ConfigHttpMethod::Options => hyper::Method::OPTIONS,
+        ConfigHttpMethod::Patch => hyper::Method::PATCH,
+        ConfigHttpMethod::Custom(name) => hyper::Method::from_bytes(name.as_bytes())
+            .map_err(|_| format!("Invalid HTTP method '{}'", name))?,
+    })
 }
 
-// ─── HTTP method mapping ──────────────────────────────────────────────────────
+// ─── Request body ─────────────────────────────────────────────────────────────
+
+/// The encoded payload, built once so 307/308 redirects can replay it.
+/// Content-Type applies unless a custom header sets it; hyper sets Content-Length.
+struct RequestBody {
+    bytes: Vec<u8>,
+    content_type: &'static str,
+}
+
+fn content_type_for_file(path: &str) -> &'static str {
+    let extension = std::path::Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("json") => "application/json",
+        Some("xml") => "application/xml",
+        Some("html" | "htm") => "text/html; charset=utf-8",
+        Some("txt") => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
 
-fn method_str(method: &ConfigHttpMethod) -> &'static str {
-    match method {
-        ConfigHttpMethod::Get     => "GET",
-        ConfigHttpMethod::Post    => "POST",
-        ConfigHttpMethod::Put     => "PUT",
-        ConfigHttpMethod::Delete  => "DELETE",
-        ConfigHttpMethod::Head    => "HEAD",
-        ConfigHttpMethod::Options => "OPTIONS",
+async fn encode_body(config: &HttpCheck) -> Result<Option<RequestBody>, String> {
+    let configured = [
+        config.body.is_some(),
+        config.body_file.is_some(),
+        config.json.is_some(),
+        config.form.is_some(),
+    ];
+    if configured.iter().filter(|set| **set).count() > 1 {
+        return Err("Only one of body, body_file, json or form may be set".to_string());
     }
+
+    let body = if let Some(body) = &config.body {
+        RequestBody {
+            bytes: body.clone().into_bytes(),
+            content_type: "text/plain; charset=utf-8",
+        }
+    } else if let Some(path) = &config.body_file {
+        let bytes = tokio::fs::read(path)
+            .await
+            .map_err(|e| format!("Failed to read body_file '{}': {}", path, e))?;
+        RequestBody {
+            bytes,
+            content_type: content_type_for_file(path),
+        }
+    } else if let Some(json) = &config.json {
+        let bytes = serde_json::to_vec(json).map_err(|e| format!("Invalid json body: {}", e))?;
+        RequestBody {
+            bytes,
+            content_type: "application/json",
+        }
+    } else if let Some(form) = &config.form {
+        let encoded =
+            serde_urlencoded::to_string(form).map_err(|e| format!("Invalid form body: {}", e))?;
+        RequestBody {
+            bytes: encoded.into_bytes(),
+            content_type: "application/x-www-form-urlencoded",
+        }
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(body))
 }
 
 // ─── Public entry point ───────────────────────────────────────────────────────
@@ -358,17 +532,148 @@ pub async fn check_http_target(
     address: &str,
     http_check_config: &HttpCheck,
 ) -> HttpTargetCheckResult {
+    if !http_check_config.check_each_address {
+        let client = http_client(
+            address,
+            http_check_config.port,
+            client_options(http_check_config, None),
+        );
+        return check_with_client(address, http_check_config, &client).await;
+    }
+
     let start_time = Instant::now();
+    // Through a proxy the addresses are the proxy's business
+    let addrs = match proxy::select(
+        http_check_config.proxy.as_ref(),
+        address,
+        http_check_config.port,
+    ) {
+        Ok(Some(proxy)) => Err(format!(
+            "check_each_address cannot be used through proxy {}",
+            proxy
+        )),
+        Ok(None) => {
+            net::resolve(
+                address,
+                http_check_config.port,
+                http_check_config.address_family,
+            )
+            .await
+        }
+        Err(e) => Err(e),
+    };
+    let addrs = match addrs {
+        Ok(addrs) => addrs,
+        Err(e) => {
+            return HttpTargetCheckResult {
+                status: CheckStatus::Unhealthy(e),
+                response_time_ms: start_time.elapsed().as_millis(),
+                cert_days_remaining: None,
+                cert_is_valid: None,
+                redirects: Vec::new(),
+                timings: None,
+                addresses: Vec::new(),
+                oauth2_token_ok: None,
+                assertions: Vec::new(),
+            };
+        }
+    };
+    check_each_address(address, http_check_config, addrs).await
+}
 
-    // 1. SSL certificate probe
-    let (cert_days_remaining, cert_is_valid) = if http_check_config.protocol == HttpProtocol::Https {
-        info!("Probing SSL certificate for {} on port {}", address, http_check_config.port);
-        probe_ssl_cert(address, http_check_config.port)
+/// Run the check once per resolved address, each on a client pinned to that
+/// address, so every backend behind round-robin DNS is covered. The target is
+/// healthy only if every address is; the details come from the first address.
+async fn check_each_address(
+    address: &str,
+    http_check_config: &HttpCheck,
+    addrs: Vec<SocketAddr>,
+) -> HttpTargetCheckResult {
+    let host = address.trim_matches(|c| c == '[' || c == ']').to_string();
+    let mut checks = tokio::task::JoinSet::new();
+    for (index, addr) in addrs.iter().enumerate() {
+        let client = http_client(
+            address,
+            http_check_config.port,
+            client_options(http_check_config, Some((host.clone(), addr.ip()))),
+        );
+        let (address, config) = (address.to_string(), http_check_config.clone());
+        checks.spawn(async move { (index, check_with_client(&address, &config, &client).await) });
+    }
+    let mut results: Vec<Option<HttpTargetCheckResult>> = vec![None; addrs.len()];
+    while let Some(joined) = checks.join_next().await {
+        if let Ok((index, result)) = joined {
+            results[index] = Some(result);
+        }
+    }
+
+    let addresses: Vec<AddressCheckResult> = addrs
+        .iter()
+        .zip(&results)
+        .map(|(addr, result)| match result {
+            Some(r) => AddressCheckResult {
+                address: addr.ip().to_string(),
+                status: r.status.clone(),
+                response_time_ms: r.response_time_ms,
+            },
+            None => AddressCheckResult {
+                address: addr.ip().to_string(),
+                status: CheckStatus::Unhealthy("Check task failed".to_string()),
+                response_time_ms: 0,
+            },
+        })
+        .collect();
+    let failures: Vec<String> = addresses
+        .iter()
+        .filter_map(|a| match &a.status {
+            CheckStatus::Unhealthy(e) => Some(format!("{}: {}", a.address, e)),
+            CheckStatus::Healthy => None,
+        })
+        .collect();
+
+    let mut combined = results
+        .into_iter()
+        .flatten()
+        .next()
+        .unwrap_or(HttpTargetCheckResult {
+            status: CheckStatus::Healthy,
+            response_time_ms: 0,
+            cert_days_remaining: None,
+            cert_is_valid: None,
+            redirects: Vec::new(),
+            timings: None,
+            addresses: Vec::new(),
+            oauth2_token_ok: None,
+            assertions: Vec::new(),
+        });
+    combined.status = if failures.is_empty() {
+        CheckStatus::Healthy
     } else {
-        (None, None)
+        CheckStatus::Unhealthy(format!(
+            "{} of {} addresses failed: {}",
+            failures.len(),
+            addresses.len(),
+            failures.join("; ")
+        ))
     };
+    combined.response_time_ms = addresses
+        .iter()
+        .map(|a| a.response_time_ms)
+        .max()
+        .unwrap_or(0);
+    combined.addresses = addresses;
+    combined
+}
 
-    // 2. Build extra headers (custom + auth)
+async fn check_with_client(
+    address: &str,
+    http_check_config: &HttpCheck,
+    client: &HttpClient,
+) -> HttpTargetCheckResult {
+    let start_time = Instant::now();
+    let is_https = http_check_config.protocol == HttpProtocol::Https;
+
+    // 1. Build extra headers (custom + auth)
     let mut extra_headers: Vec<(String, String)> = Vec::new();
 
     if let Some(headers) = &http_check_config.headers {
@@ -377,65 +682,106 @@ pub async fn check_http_target(
         }
     }
 
-    if let Some(auth) = &http_check_config.auth {
-        match auth {
-            AuthConfig::Basic { username, password } => {
-                use base64::Engine as _;
-                let encoded = base64::engine::general_purpose::STANDARD
-                    .encode(format!("{}:{}", username, password));
-                extra_headers.push(("Authorization".into(), format!("Basic {}", encoded)));
-            }
-            AuthConfig::Bearer { token } => {
-                extra_headers.push(("Authorization".into(), format!("Bearer {}", token)));
-            }
-            AuthConfig::OAuth2 { client_id, client_secret, token_url } => {
-                match get_oauth2_token(client_id, client_secret, token_url).await {
-                    Ok(token) => {
-                        extra_headers.push(("Authorization".into(), format!("Bearer {}", token)));
-                    }
-                    Err(e) => {
-                        return HttpTargetCheckResult {
-                            status: CheckStatus::Unhealthy(format!("OAuth2 authentication failed: {}", e)),
-                            response_time_ms: start_time.elapsed().as_millis(),
-                            cert_days_remaining,
-                            cert_is_valid,
-                        };
-                    }
-                }
-            }
-        }
-    }
+    let oauth2 = match &http_check_config.auth {
+        Some(AuthConfig::OAuth2(oauth2)) => Some(oauth2),
+        _ => None,
+    };
+    let token_failure = |e: String| HttpTargetCheckResult {
+        status: CheckStatus::Unhealthy(e),
+        response_time_ms: start_time.elapsed().as_millis(),
+        cert_days_remaining: None,
+        cert_is_valid: None,
+        redirects: Vec::new(),
+        timings: None,
+        addresses: Vec::new(),
+        oauth2_token_ok: oauth2.map(|_| false),
+        assertions: Vec::new(),
+    };
+    // Reached only once any OAuth2 token has been obtained
+    let token_ok = oauth2.map(|_| true);
 
-    // 3. Perform the request
-    let is_https       = http_check_config.protocol == HttpProtocol::Https;
-    let accept_invalid = is_https;
+    let mut request_auth = match &http_check_config.auth {
+        Some(auth) => match RequestAuth::new(auth, http_check_config.proxy.as_ref()).await {
+            Ok(request_auth) => Some(request_auth),
+            Err(e) => return token_failure(e),
+        },
+        None => None,
+    };
 
-    let response = match do_http_request(
-        address,
+    // 2. Perform the request on a shared client
+    let host = if address.contains(':') && !address.starts_with('[') {
+        format!("[{}]", address) // IPv6 literal
+    } else {
+        address.to_string()
+    };
+    let url = format!(
+        "{}://{}:{}{}",
+        if is_https { "https" } else { "http" },
+        host,
         http_check_config.port,
-        &http_check_config.path,
-        method_str(&http_check_config.method),
+        http_check_config.path
+    );
+
+    let mut request_start = Instant::now();
+    let mut outcome = send_request(
+        client,
+        http_check_config,
+        &url,
         &extra_headers,
-        Duration::from_secs(http_check_config.timeout_seconds),
-        is_https,
-        accept_invalid,
-    ) {
+        request_auth.as_mut(),
+    )
+    .await;
+
+    // A rejected token may have been revoked or rotated early: retry once with a fresh one
+    let rejected = matches!(&outcome, Ok(r) if r.status_code == 401 && http_check_config.expected_status_code != 401);
+    if let (Some(auth @ AuthConfig::OAuth2(oauth2)), true) = (&http_check_config.auth, rejected) {
+        info!("OAuth2 token rejected by {}, fetching a new one", url);
+        invalidate_oauth2_token(oauth2);
+        request_auth = match RequestAuth::new(auth, http_check_config.proxy.as_ref()).await {
+            Ok(request_auth) => Some(request_auth),
+            Err(e) => return token_failure(e),
+        };
+        request_start = Instant::now();
+        outcome = send_request(
+            client,
+            http_check_config,
+            &url,
+            &extra_headers,
+            request_auth.as_mut(),
+        )
+        .await;
+    }
+    let response_time_ms = request_start.elapsed().as_millis();
+
+    let response = match outcome {
         Ok(r) => r,
         Err(e) => {
             return HttpTargetCheckResult {
-                status: CheckStatus::Unhealthy(format!(
-                    "Request to {}://{}:{}{} failed: {}",
-                    if is_https { "https" } else { "http" },
-                    address, http_check_config.port, http_check_config.path, e
-                )),
-                response_time_ms: start_time.elapsed().as_millis(),
-                cert_days_remaining,
-                cert_is_valid,
+                status: CheckStatus::Unhealthy(format!("Request to {} failed: {}", url, e)),
+                response_time_ms,
+                cert_days_remaining: None,
+                cert_is_valid: if is_https { Some(false) } else { None },
+                redirects: Vec::new(),
+                timings: None,
+                addresses: Vec::new(),
+                oauth2_token_ok: token_ok,
+                assertions: Vec::new(),
             };
         }
     };
 
-    let response_time_ms = start_time.elapsed().as_millis();
+    // 3. Certificate expiry from the handshake of the connection that served the request
+    let (cert_days_remaining, cert_is_valid) = match &response.certificate {
+        Some(cert) => {
+            info!(
+                "SSL cert for {}: {:?} days remaining",
+                address, cert.days_remaining
+            );
+            (cert.days_remaining, Some(cert.is_valid))
+        }
+        None if is_https => (None, Some(false)),
+        None => (None, None),
+    };
 
     // 4. Status code check
     if response.status_code != http_check_config.expected_status_code {
@@ -447,6 +793,11 @@ pub async fn check_http_target(
             response_time_ms,
             cert_days_remaining,
             cert_is_valid,
+            redirects: response.redirects,
+            timings: Some(response.timings),
+            addresses: Vec::new(),
+            oauth2_token_ok: token_ok,
+            assertions: Vec::new(),
         };
     }
 
@@ -463,34 +814,47 @@ pub async fn check_http_target(
                         response_time_ms,
                         cert_days_remaining,
                         cert_is_valid,
+                        redirects: response.redirects,
+                        timings: Some(response.timings),
+                        addresses: Vec::new(),
+                        oauth2_token_ok: token_ok,
+                        assertions: Vec::new(),
                     };
                 }
             }
             Err(e) => {
                 return HttpTargetCheckResult {
                     status: CheckStatus::Unhealthy(format!(
-                        "Invalid regex pattern: '{}' - {}", regex_pattern, e
+                        "Invalid regex pattern: '{}' - {}",
+                        regex_pattern, e
                     )),
                     response_time_ms,
                     cert_days_remaining,
                     cert_is_valid,
+                    redirects: response.redirects,
+                    timings: Some(response.timings),
+                    addresses: Vec::new(),
+                    oauth2_token_ok: token_ok,
+                    assertions: Vec::new(),
                 };
             }
         }
     }
 
     // 6. Assertions
+    let mut assertion_results = Vec::new();
     if let Some(assertions) = &http_check_config.assertions {
-        let http_status = http::StatusCode::from_u16(response.status_code)
-            .unwrap_or(http::StatusCode::OK);
+        let http_status =
+            http::StatusCode::from_u16(response.status_code).unwrap_or(http::StatusCode::OK);
 
-        let assertion_results = evaluate_assertions_with_data(
+        assertion_results = evaluate_assertions_with_data(
             assertions,
             http_status,
             &response.headers,
             &response.body,
             response_time_ms,
-            None,
+            response.certificate.as_ref(),
+            &response.redirects,
         );
 
         let failed: Vec<_> = assertion_results.iter().filter(|r| !r.passed).collect();
@@ -501,6 +865,11 @@ pub async fn check_http_target(
                 response_time_ms,
                 cert_days_remaining,
                 cert_is_valid,
+                redirects: response.redirects,
+                timings: Some(response.timings),
+                addresses: Vec::new(),
+                oauth2_token_ok: token_ok,
+                assertions: assertion_results,
             };
         }
     }
@@ -510,5 +879,49 @@ pub async fn check_http_target(
         response_time_ms,
         cert_days_remaining,
         cert_is_valid,
+        redirects: response.redirects,
+        timings: Some(response.timings),
+        addresses: Vec::new(),
+        oauth2_token_ok: token_ok,
+        assertions: assertion_results,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(target: &str) -> ClientKey {
+        ClientKey {
+            target: target.to_string(),
+            options: ClientOptions::default(),
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_client_cache_expires_and_bounds_clients() {
+        let mut cache = ClientCache::default();
+        let start = Instant::now();
+        cache.get(key("a:80"), start);
+        cache.get(key("b:80"), start + Duration::from_secs(60));
+        assert_eq!(cache.clients.len(), 2);
+
+        // "a" has been idle past the expiry, "b" has not
+        cache.get(key("b:80"), start + CLIENT_IDLE_EXPIRY);
+        assert!(!cache.clients.contains_key(&key("a:80")));
+        assert!(cache.clients.contains_key(&key("b:80")));
+
+        let now = start + CLIENT_IDLE_EXPIRY;
+        for i in 0..MAX_CLIENTS + 10 {
+            cache.get(
+                key(&format!("host{}:80", i)),
+                now + Duration::from_millis(i as u64),
+            );
+        }
+        assert_eq!(cache.clients.len(), MAX_CLIENTS);
+        assert!(cache
+            .clients
+            .contains_key(&key(&format!("host{}:80", MAX_CLIENTS + 9))));
+        assert!(!cache.clients.contains_key(&key("host0:80")));
+    }
+}
@@ -0,0 +1,716 @@
+use crate::config::{AddressFamily, MailCheck, TlsClientConfig};
+use crate::monitoring::net::connect_blocking;
+use crate::monitoring::tls::{connect_tls, MaybeTlsStream, TlsCertInfo};
+use crate::monitoring::types::{CheckStatus, MailCheckResult};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+// ─── Protocol selection ───────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum MailProtocol {
+    Smtp,
+    Imap,
+    Pop3,
+}
+
+impl MailProtocol {
+    fn label(self) -> &'static str {
+        match self {
+            MailProtocol::Smtp => "SMTP",
+            MailProtocol::Imap => "IMAP",
+            MailProtocol::Pop3 => "POP3",
+        }
+    }
+}
+
+// ─── Line-oriented session over plain TCP or TLS ─────────────────────────────
+
+const MAX_LINE_LENGTH: usize = 8192;
+
+struct MailSession {
+    stream: Option<MaybeTlsStream>,
+    buffer: Vec<u8>,
+    cert: Option<TlsCertInfo>,
+    tls: TlsClientConfig,
+    imap_tag: u32,
+}
+
+impl MailSession {
+    fn connect(
+        address: &str,
+        port: u16,
+        family: AddressFamily,
+        timeout: Duration,
+        tls: TlsClientConfig,
+    ) -> Result<Self, String> {
+        let tcp = connect_blocking(address, port, family, timeout)?;
+        tcp.set_read_timeout(Some(timeout))
+            .map_err(|e| format!("set_read_timeout: {}", e))?;
+        tcp.set_write_timeout(Some(timeout))
+            .map_err(|e| format!("set_write_timeout: {}", e))?;
+        tcp.set_nodelay(true).ok();
+        Ok(MailSession::new(tcp, tls))
+    }
+
+    fn new(tcp: TcpStream, tls: TlsClientConfig) -> Self {
+        MailSession {
+            stream: Some(MaybeTlsStream::Plain(tcp)),
+            buffer: Vec::with_capacity(4096),
+            cert: None,
+            tls,
+            imap_tag: 0,
+        }
+    }
+
+    fn stream(&mut self) -> Result<&mut MaybeTlsStream, String> {
+        self.stream
+            .as_mut()
+            .ok_or_else(|| "Connection is no longer usable".to_string())
+    }
+
+    fn send_line(&mut self, line: &str) -> Result<(), String> {
+        let stream = self.stream()?;
+        stream
+            .write_all(format!("{}\r\n", line).as_bytes())
+            .and_then(|_| stream.flush())
+            .map_err(|e| format!("Write failed: {}", e))
+    }
+
+    fn read_line(&mut self) -> Result<String, String> {
+        loop {
+            if let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+                return Ok(String::from_utf8_lossy(&line)
+                    .trim_end_matches(['\r', '\n'])
+                    .to_string());
+            }
+            if self.buffer.len() > MAX_LINE_LENGTH {
+                return Err("Server response line too long".into());
+            }
+
+            let mut tmp = [0u8; 4096];
+            let n = self
+                .stream()?
+                .read(&mut tmp)
+                .map_err(|e| format!("Read failed: {}", e))?;
+            if n == 0 {
+                return Err("Connection closed by server".into());
+            }
+            self.buffer.extend_from_slice(&tmp[..n]);
+        }
+    }
+
+    /// Upgrade the current plaintext connection to TLS, recording the peer
+    /// certificate details. Unless verification is turned off, an untrusted
+    /// server is refused before any credentials are sent.
+    fn start_tls(&mut self, host: &str) -> Result<(), String> {
+        // Anything already buffered was sent in plaintext before the handshake
+        // and must not be treated as part of the encrypted session.
+        if !self.buffer.is_empty() {
+            return Err("Unexpected data received before TLS negotiation".into());
+        }
+
+        let tcp = match self.stream.take() {
+            Some(MaybeTlsStream::Plain(tcp)) => tcp,
+            Some(tls @ MaybeTlsStream::Tls(_)) => {
+                self.stream = Some(tls);
+                return Err("TLS is already active on this connection".into());
+            }
+            None => return Err("Connection is no longer usable".into()),
+        };
+
+        let (tls, cert) = connect_tls(host, tcp, &self.tls, self.tls.verify_certificate)?;
+        self.cert = Some(cert);
+        self.stream = Some(MaybeTlsStream::Tls(Box::new(tls)));
+        Ok(())
+    }
+
+    // ── SMTP ─────────────────────────────────────────────────────────────────
+
+    /// Read a (possibly multi-line) SMTP reply, returning the code and text lines.
+    fn read_smtp_reply(&mut self) -> Result<(u16, Vec<String>), String> {
+        let mut lines = Vec::new();
+        loop {
+            let line = self.read_line()?;
+            let code = line
+                .get(..3)
+                .and_then(|c| c.parse::<u16>().ok())
+                .ok_or_else(|| format!("Malformed SMTP reply: '{}'", line))?;
+            lines.push(line.get(4..).unwrap_or("").to_string());
+            if line.as_bytes().get(3) != Some(&b'-') {
+                return Ok((code, lines));
+            }
+        }
+    }
+
+    fn smtp_command(&mut self, command: &str, expected: u16) -> Result<Vec<String>, String> {
+        self.send_line(command)?;
+        let (code, lines) = self.read_smtp_reply()?;
+        if code != expected {
+            return Err(format!(
+                "{} returned {} {}",
+                command_verb(command),
+                code,
+                lines.join(" ")
+            ));
+        }
+        Ok(lines)
+    }
+
+    // ── IMAP ─────────────────────────────────────────────────────────────────
+
+    /// Send a tagged IMAP command and return the untagged lines on `OK`.
+    fn imap_command(&mut self, command: &str) -> Result<Vec<String>, String> {
+        self.imap_tag += 1;
+        let tag = format!("a{}", self.imap_tag);
+        self.send_line(&format!("{} {}", tag, command))?;
+
+        let mut untagged = Vec::new();
+        loop {
+            let line = self.read_line()?;
+            if let Some(rest) = line.strip_prefix(&format!("{} ", tag)) {
+                return if rest.to_uppercase().starts_with("OK") {
+                    Ok(untagged)
+                } else {
+                    Err(format!("{} failed: {}", command_verb(command), rest))
+                };
+            }
+            untagged.push(line);
+        }
+    }
+
+    // ── POP3 ─────────────────────────────────────────────────────────────────
+
+    fn pop3_command(&mut self, command: &str) -> Result<String, String> {
+        self.send_line(command)?;
+        let line = self.read_line()?;
+        match line.strip_prefix("+OK") {
+            Some(rest) => Ok(rest.trim().to_string()),
+            None => Err(format!("{} failed: {}", command_verb(command), line)),
+        }
+    }
+
+    /// Send a POP3 command answered with a dot-terminated list of lines.
+    fn pop3_multiline(&mut self, command: &str) -> Result<Vec<String>, String> {
+        self.pop3_command(command)?;
+        let mut lines = Vec::new();
+        loop {
+            let line = self.read_line()?;
+            if line == "." {
+                return Ok(lines);
+            }
+            lines.push(line.strip_prefix('.').unwrap_or(&line).to_string());
+        }
+    }
+}
+
+/// First word of a command, used in error messages so credentials never leak.
+fn command_verb(command: &str) -> &str {
+    command.split_whitespace().next().unwrap_or(command)
+}
+
+fn has_capability(lines: &[String], capability: &str) -> bool {
+    lines
+        .iter()
+        .flat_map(|l| l.split_whitespace())
+        .any(|word| word.eq_ignore_ascii_case(capability))
+}
+
+fn imap_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// ─── Protocol dialogues ───────────────────────────────────────────────────────
+
+/// Read the SMTP greeting and say EHLO, returning the banner and capabilities.
+fn smtp_greet(session: &mut MailSession, ehlo: &str) -> Result<(String, Vec<String>), String> {
+    let (code, lines) = session.read_smtp_reply()?;
+    if code != 220 {
+        return Err(format!(
+            "Unexpected SMTP greeting: {} {}",
+            code,
+            lines.join(" ")
+        ));
+    }
+    let banner = lines.first().cloned().unwrap_or_default();
+    let capabilities = session.smtp_command(ehlo, 250)?;
+    Ok((banner, capabilities))
+}
+
+fn smtp_starttls(
+    session: &mut MailSession,
+    address: &str,
+    capabilities: &[String],
+) -> Result<(), String> {
+    if !has_capability(capabilities, "STARTTLS") {
+        return Err("Server does not advertise STARTTLS".into());
+    }
+    session.smtp_command("STARTTLS", 220)?;
+    session.start_tls(address)
+}
+
+fn smtp_dialogue(
+    session: &mut MailSession,
+    address: &str,
+    config: &MailCheck,
+) -> Result<String, String> {
+    let ehlo = format!(
+        "EHLO {}",
+        config.ehlo_hostname.as_deref().unwrap_or("localhost")
+    );
+    let (banner, mut capabilities) = smtp_greet(session, &ehlo)?;
+
+    if config.starttls {
+        smtp_starttls(session, address, &capabilities)?;
+        capabilities = session.smtp_command(&ehlo, 250)?;
+    }
+
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        if !capabilities
+            .iter()
+            .any(|c| c.to_uppercase().starts_with("AUTH"))
+        {
+            return Err("Server does not advertise AUTH".into());
+        }
+        use base64::Engine as _;
+        let token = base64::engine::general_purpose::STANDARD.encode(format!(
+            "\0{}\0{}",
+            username,
+            password.expose()
+        ));
+        session
+            .smtp_command(&format!("AUTH PLAIN {}", token), 235)
+            .map_err(|e| format!("Authentication failed: {}", e))?;
+    }
+
+    let _ = session.smtp_command("QUIT", 221);
+    Ok(banner)
+}
+
+/// Read the IMAP greeting and ask for capabilities, returning the banner and
+/// the capability lines.
+fn imap_greet(session: &mut MailSession) -> Result<(String, Vec<String>), String> {
+    let greeting = session.read_line()?;
+    let banner = match greeting
+        .strip_prefix("* OK")
+        .or_else(|| greeting.strip_prefix("* PREAUTH"))
+    {
+        Some(rest) => rest.trim().to_string(),
+        None => return Err(format!("Unexpected IMAP greeting: {}", greeting)),
+    };
+    let capabilities = session.imap_command("CAPABILITY")?;
+    Ok((banner, capabilities))
+}
+
+fn imap_starttls(
+    session: &mut MailSession,
+    address: &str,
+    capabilities: &[String],
+) -> Result<(), String> {
+    if !has_capability(capabilities, "STARTTLS") {
+        return Err("Server does not advertise STARTTLS".into());
+    }
+    session.imap_command("STARTTLS")?;
+    session.start_tls(address)
+}
+
+fn imap_dialogue(
+    session: &mut MailSession,
+    address: &str,
+    config: &MailCheck,
+) -> Result<String, String> {
+    let (banner, capabilities) = imap_greet(session)?;
+
+    if config.starttls {
+        imap_starttls(session, address, &capabilities)?;
+        session.imap_command("CAPABILITY")?;
+    }
+
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        session
+            .imap_command(&format!(
+                "LOGIN {} {}",
+                imap_quote(username),
+                imap_quote(password.expose())
+            ))
+            .map_err(|e| format!("Authentication failed: {}", e))?;
+    }
+
+    let _ = session.imap_command("LOGOUT");
+    Ok(banner)
+}
+
+fn pop3_greet(session: &mut MailSession) -> Result<String, String> {
+    let greeting = session.read_line()?;
+    match greeting.strip_prefix("+OK") {
+        Some(rest) => Ok(rest.trim().to_string()),
+        None => Err(format!("Unexpected POP3 greeting: {}", greeting)),
+    }
+}
+
+fn pop3_starttls(session: &mut MailSession, address: &str) -> Result<(), String> {
+    // A server without CAPA (RFC 2449) cannot advertise STLS either
+    let capabilities = session.pop3_multiline("CAPA").unwrap_or_default();
+    if !has_capability(&capabilities, "STLS") {
+        return Err("Server does not advertise STLS".into());
+    }
+    session.pop3_command("STLS")?;
+    session.start_tls(address)
+}
+
+fn pop3_dialogue(
+    session: &mut MailSession,
+    address: &str,
+    config: &MailCheck,
+) -> Result<String, String> {
+    let banner = pop3_greet(session)?;
+
+    if config.starttls {
+        pop3_starttls(session, address)?;
+    }
+
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        session
+            .pop3_command(&format!("USER {}", username))
+            .and_then(|_| session.pop3_command(&format!("PASS {}", password.expose())))
+            .map_err(|e| format!("Authentication failed: {}", e))?;
+    }
+
+    let _ = session.pop3_command("QUIT");
+    Ok(banner)
+}
+
+/// Upgrade a fresh connection with STARTTLS (STLS for POP3) and return the
+/// certificate details, for the `Tls` check to judge.
+pub(crate) fn starttls_certificate(
+    tcp: TcpStream,
+    address: &str,
+    protocol: MailProtocol,
+    tls: TlsClientConfig,
+) -> Result<TlsCertInfo, String> {
+    let mut session = MailSession::new(tcp, tls);
+    match protocol {
+        MailProtocol::Smtp => {
+            let (_, capabilities) = smtp_greet(&mut session, "EHLO localhost")?;
+            smtp_starttls(&mut session, address, &capabilities)?;
+        }
+        MailProtocol::Imap => {
+            let (_, capabilities) = imap_greet(&mut session)?;
+            imap_starttls(&mut session, address, &capabilities)?;
+        }
+        MailProtocol::Pop3 => {
+            pop3_greet(&mut session)?;
+            pop3_starttls(&mut session, address)?;
+        }
+    }
+    if let Some(MaybeTlsStream::Tls(tls)) = session.stream.as_mut() {
+        let _ = tls.shutdown();
+    }
+    session
+        .cert
+        .ok_or_else(|| "TLS was not negotiated".to_string())
+}
+
+fn run_mail_session(
+    address: &str,
+    config: &MailCheck,
+    protocol: MailProtocol,
+) -> (Result<String, String>, Option<TlsCertInfo>) {
+    let mut session = match MailSession::connect(
+        address,
+        config.port,
+        config.address_family,
+        Duration::from_secs(config.timeout_seconds),
+        config.tls.clone().unwrap_or_default(),
+    ) {
+        Ok(s) => s,
+        Err(e) => return (Err(e), None),
+    };
+
+    let outcome = (|| {
+        if config.use_ssl {
+            session.start_tls(address)?;
+        }
+        match protocol {
+            MailProtocol::Smtp => smtp_dialogue(&mut session, address, config),
+            MailProtocol::Imap => imap_dialogue(&mut session, address, config),
+            MailProtocol::Pop3 => pop3_dialogue(&mut session, address, config),
+        }
+    })();
+
+    (outcome, session.cert)
+}
+
+// ─── Public entry points ──────────────────────────────────────────────────────
+
+async fn check_mail_server(
+    address: &str,
+    config: &MailCheck,
+    protocol: MailProtocol,
+) -> MailCheckResult {
+    let start_time = Instant::now();
+
+    let addr = address.to_string();
+    let cfg = config.clone();
+    let result = tokio::task::spawn_blocking(move || run_mail_session(&addr, &cfg, protocol)).await;
+
+    let (outcome, cert) = match result {
+        Ok(r) => r,
+        Err(e) => (Err(format!("task failed: {}", e)), None),
+    };
+    let (cert_days_remaining, cert_is_valid) = match cert {
+        Some(c) => (c.days_remaining, Some(c.is_valid)),
+        None => (None, None),
+    };
+
+    match outcome {
+        Ok(banner) => MailCheckResult {
+            status: CheckStatus::Healthy,
+            response_time_ms: start_time.elapsed().as_millis(),
+            service_info: Some(format!("{}: {}", protocol.label(), banner)),
+            cert_days_remaining,
+            cert_is_valid,
+        },
+        Err(e) => MailCheckResult {
+            status: CheckStatus::Unhealthy(format!("{} check failed: {}", protocol.label(), e)),
+            response_time_ms: start_time.elapsed().as_millis(),
+            service_info: None,
+            cert_days_remaining,
+            cert_is_valid,
+        },
+    }
+}
+
+pub async fn check_smtp(address: &str, config: &MailCheck) -> MailCheckResult {
+    check_mail_server(address, config, MailProtocol::Smtp).await
+}
+
+pub async fn check_imap(address: &str, config: &MailCheck) -> MailCheckResult {
+    check_mail_server(address, config, MailProtocol::Imap).await
+}
+
+pub async fn check_pop3(address: &str, config: &MailCheck) -> MailCheckResult {
+    check_mail_server(address, config, MailProtocol::Pop3).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitoring::tls::self_signed_cert;
+    use openssl::ssl::{SslAcceptor, SslMethod};
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    /// Serve a scripted plaintext conversation: send the greeting, then for each
+    /// (expected command prefix, reply) pair read one line and answer it.
+    fn spawn_fake_server(greeting: &'static str, script: Vec<(&'static str, &'static str)>) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            stream.write_all(greeting.as_bytes()).unwrap();
+            for (expected, reply) in script {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    return;
+                }
+                assert!(line.starts_with(expected), "unexpected command: {}", line);
+                stream.write_all(reply.as_bytes()).unwrap();
+            }
+        });
+        port
+    }
+
+    fn mail_check(port: u16) -> MailCheck {
+        MailCheck {
+            name: None,
+            port,
+            timeout_seconds: 5,
+            use_ssl: false,
+            starttls: false,
+            username: None,
+            password: None,
+            ehlo_hostname: None,
+            address_family: AddressFamily::Both,
+            tls: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_smtp_greeting_ehlo_and_auth() {
+        let port = spawn_fake_server(
+            "220 mx.example.com ESMTP ready\r\n",
+            vec![
+                (
+                    "EHLO localhost",
+                    "250-mx.example.com\r\n250-AUTH PLAIN LOGIN\r\n250 SIZE 1000\r\n",
+                ),
+                ("AUTH PLAIN ", "235 2.7.0 Authentication successful\r\n"),
+                ("QUIT", "221 Bye\r\n"),
+            ],
+        );
+        let mut config = mail_check(port);
+        config.username = Some("monitor".to_string());
+        config.password = Some("secret".into());
+
+        let result = check_smtp("127.0.0.1", &config).await;
+        assert!(
+            matches!(result.status, CheckStatus::Healthy),
+            "{:?}",
+            result.status
+        );
+        assert_eq!(
+            result.service_info.as_deref(),
+            Some("SMTP: mx.example.com ESMTP ready")
+        );
+        assert_eq!(result.cert_days_remaining, None);
+        assert_eq!(result.cert_is_valid, None);
+    }
+
+    #[tokio::test]
+    async fn test_smtp_starttls_not_advertised() {
+        let port = spawn_fake_server(
+            "220 mx.example.com ESMTP\r\n",
+            vec![("EHLO", "250-mx.example.com\r\n250 SIZE 1000\r\n")],
+        );
+        let mut config = mail_check(port);
+        config.starttls = true;
+
+        let result = check_smtp("127.0.0.1", &config).await;
+        match result.status {
+            CheckStatus::Unhealthy(msg) => assert!(msg.contains("STARTTLS"), "{}", msg),
+            CheckStatus::Healthy => panic!("expected STARTTLS failure"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_imap_login_failure_hides_password() {
+        let port = spawn_fake_server(
+            "* OK IMAP4rev1 ready\r\n",
+            vec![
+                (
+                    "a1 CAPABILITY",
+                    "* CAPABILITY IMAP4rev1 AUTH=PLAIN\r\na1 OK done\r\n",
+                ),
+                (
+                    "a2 LOGIN",
+                    "a2 NO [AUTHENTICATIONFAILED] Invalid credentials\r\n",
+                ),
+            ],
+        );
+        let mut config = mail_check(port);
+        config.username = Some("monitor".to_string());
+        config.password = Some("hunter2".into());
+
+        let result = check_imap("127.0.0.1", &config).await;
+        match result.status {
+            CheckStatus::Unhealthy(msg) => {
+                assert!(msg.contains("Authentication failed"), "{}", msg);
+                assert!(!msg.contains("hunter2"));
+            }
+            CheckStatus::Healthy => panic!("expected login failure"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pop3_user_pass() {
+        let port = spawn_fake_server(
+            "+OK POP3 server ready\r\n",
+            vec![
+                ("USER monitor", "+OK\r\n"),
+                ("PASS secret", "+OK logged in\r\n"),
+                ("QUIT", "+OK bye\r\n"),
+            ],
+        );
+        let mut config = mail_check(port);
+        config.username = Some("monitor".to_string());
+        config.password = Some("secret".into());
+
+        let result = check_pop3("127.0.0.1", &config).await;
+        assert!(
+            matches!(result.status, CheckStatus::Healthy),
+            "{:?}",
+            result.status
+        );
+        assert_eq!(
+            result.service_info.as_deref(),
+            Some("POP3: POP3 server ready")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pop3_stls_requires_capability() {
+        let port = spawn_fake_server(
+            "+OK POP3 server ready\r\n",
+            vec![("CAPA", "+OK\r\nUSER\r\nUIDL\r\n.\r\n")],
+        );
+        let mut config = mail_check(port);
+        config.starttls = true;
+        config.username = Some("monitor".to_string());
+        config.password = Some("secret".into());
+
+        let result = check_pop3("127.0.0.1", &config).await;
+        match result.status {
+            CheckStatus::Unhealthy(msg) => {
+                assert!(msg.contains("does not advertise STLS"), "{}", msg)
+            }
+            CheckStatus::Healthy => panic!("expected STLS failure"),
+        }
+    }
+
+    /// Accept one implicit-TLS POP3 connection with a self-signed certificate.
+    fn spawn_pop3s_server() -> u16 {
+        let (cert, key) = self_signed_cert();
+        let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+        acceptor.set_certificate(&cert).unwrap();
+        acceptor.set_private_key(&key).unwrap();
+        let acceptor = acceptor.build();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let Ok(mut tls) = acceptor.accept(stream) else {
+                return;
+            };
+            tls.write_all(b"+OK POP3 server ready\r\n").unwrap();
+            let mut buf = [0u8; 64];
+            if tls.read(&mut buf).unwrap_or(0) > 0 {
+                let _ = tls.write_all(b"+OK bye\r\n");
+            }
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn test_mail_tls_verifies_certificate() {
+        let mut config = mail_check(spawn_pop3s_server());
+        config.use_ssl = true;
+        let result = check_pop3("127.0.0.1", &config).await;
+        match result.status {
+            CheckStatus::Unhealthy(msg) => assert!(
+                msg.contains("Certificate verification failed for 127.0.0.1"),
+                "{}",
+                msg
+            ),
+            CheckStatus::Healthy => panic!("untrusted certificate accepted"),
+        }
+
+        let mut config = mail_check(spawn_pop3s_server());
+        config.use_ssl = true;
+        config.tls = Some(TlsClientConfig {
+            verify_certificate: false,
+            ..Default::default()
+        });
+        let result = check_pop3("127.0.0.1", &config).await;
+        assert!(
+            matches!(result.status, CheckStatus::Healthy),
+            "{:?}",
+            result.status
+        );
+        assert_eq!(result.cert_is_valid, Some(false));
+    }
+}
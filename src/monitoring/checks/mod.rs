@@ -1,8 +1,14 @@
 pub mod database;
 pub mod http;
+pub mod mail;
 pub mod tcp;
+pub mod tls;
+pub mod websocket;
 
 // Re-export all check functions
 pub use database::*;
 pub use http::check_http_target;
+pub use mail::{check_imap, check_pop3, check_smtp};
 pub use tcp::check_tcp_port;
+pub use tls::check_tls;
+pub use websocket::check_websocket;
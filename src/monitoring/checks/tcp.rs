@@ -1,13 +1,23 @@
+use crate::config::AddressFamily;
+use crate::monitoring::net;
 use crate::monitoring::types::{CheckStatus, TcpCheckResult};
 use std::time::{Duration, Instant};
-use tokio::net::TcpStream;
 use tokio::time::timeout;
 
-pub async fn check_tcp_port(address: &str, port: u16, request_timeout: Duration) -> TcpCheckResult {
+pub async fn check_tcp_port(
+    address: &str,
+    port: u16,
+    request_timeout: Duration,
+    family: AddressFamily,
+) -> TcpCheckResult {
     let target = format!("{}:{}", address, port);
     let start_time = Instant::now();
 
-    let connect_result = timeout(request_timeout, TcpStream::connect(&target)).await;
+    let connect_result = timeout(request_timeout, async {
+        let addrs = net::resolve(address, port, family).await?;
+        net::connect(&addrs).await
+    })
+    .await;
     let response_time_ms = start_time.elapsed().as_millis();
 
     let status = match connect_result {
@@ -0,0 +1,334 @@
+use crate::config::{StartTlsProtocol, TlsCheck, TlsClientConfig, TlsVersion};
+use crate::monitoring::checks::mail::{starttls_certificate, MailProtocol};
+use crate::monitoring::net::connect_blocking;
+use crate::monitoring::tls::{connect_tls, TlsCertInfo};
+use crate::monitoring::types::{CheckStatus, TlsCheckResult};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+/// PostgreSQL `SSLRequest` message: length 8 followed by the magic code 80877103.
+const POSTGRES_SSL_REQUEST: [u8; 8] = [0x00, 0x00, 0x00, 0x08, 0x04, 0xd2, 0x16, 0x2f];
+
+// ─── Handshake ────────────────────────────────────────────────────────────────
+
+/// Ask a PostgreSQL server to switch to TLS before the startup message.
+fn postgres_ssl_request(stream: &mut TcpStream) -> Result<(), String> {
+    stream
+        .write_all(&POSTGRES_SSL_REQUEST)
+        .map_err(|e| format!("Write failed: {}", e))?;
+    let mut answer = [0u8; 1];
+    stream
+        .read_exact(&mut answer)
+        .map_err(|e| format!("Read failed: {}", e))?;
+    match answer[0] {
+        b'S' => Ok(()),
+        b'N' => Err("PostgreSQL server does not accept SSL connections".into()),
+        other => Err(format!("Unexpected SSLRequest answer: {:#x}", other)),
+    }
+}
+
+fn inspect_endpoint(address: &str, config: &TlsCheck) -> Result<TlsCertInfo, String> {
+    let timeout = Duration::from_secs(config.timeout_seconds);
+    let mut tcp = connect_blocking(address, config.port, config.address_family, timeout)?;
+    tcp.set_read_timeout(Some(timeout))
+        .map_err(|e| format!("set_read_timeout: {}", e))?;
+    tcp.set_write_timeout(Some(timeout))
+        .map_err(|e| format!("set_write_timeout: {}", e))?;
+
+    let server_name = config.server_name.as_deref().unwrap_or(address);
+    // The thresholds judge the certificate, so the handshake never refuses it
+    let options = TlsClientConfig {
+        verify_certificate: false,
+        ..config.tls.clone().unwrap_or_default()
+    };
+    match config.starttls {
+        Some(StartTlsProtocol::Smtp) => {
+            return starttls_certificate(tcp, server_name, MailProtocol::Smtp, options)
+        }
+        Some(StartTlsProtocol::Imap) => {
+            return starttls_certificate(tcp, server_name, MailProtocol::Imap, options)
+        }
+        Some(StartTlsProtocol::Postgres) => postgres_ssl_request(&mut tcp)?,
+        None => {}
+    }
+
+    let (mut tls, info) = connect_tls(server_name, tcp, &options, false)?;
+    let _ = tls.shutdown();
+    Ok(info)
+}
+
+// ─── Thresholds ───────────────────────────────────────────────────────────────
+
+fn evaluate_thresholds(config: &TlsCheck, info: &TlsCertInfo) -> Vec<String> {
+    let mut failures = Vec::new();
+
+    match (config.min_days_remaining, info.days_remaining) {
+        (Some(min), Some(days)) if days < min => failures.push(format!(
+            "certificate expires in {} days (minimum {})",
+            days, min
+        )),
+        (Some(_), None) => failures.push("certificate expiry could not be determined".into()),
+        _ => {}
+    }
+    if config.require_valid_chain && !info.chain_valid {
+        failures.push("certificate chain is not trusted".into());
+    }
+    if config.require_hostname_match && !info.hostname_matches {
+        failures.push("certificate does not match the hostname".into());
+    }
+    if let Some(expected) = &config.expected_issuer {
+        let issuer = info.issuer.as_deref().unwrap_or("");
+        if !issuer.contains(expected.as_str()) {
+            failures.push(format!(
+                "issuer '{}' does not contain '{}'",
+                issuer, expected
+            ));
+        }
+    }
+    if let Some(expected) = &config.expected_subject {
+        let subject = info.subject.as_deref().unwrap_or("");
+        if !subject.contains(expected.as_str()) {
+            failures.push(format!(
+                "subject '{}' does not contain '{}'",
+                subject, expected
+            ));
+        }
+    }
+    if let Some(required) = &config.required_sans {
+        for san in required {
+            if !info.sans.iter().any(|s| s.eq_ignore_ascii_case(san)) {
+                failures.push(format!("SAN '{}' missing", san));
+            }
+        }
+    }
+    if let Some(min) = config.min_protocol_version {
+        let negotiated = info.protocol_version.as_deref().unwrap_or("unknown");
+        match TlsVersion::from_openssl_name(negotiated) {
+            Some(version) if version >= min => {}
+            _ => failures.push(format!(
+                "negotiated protocol {} is below {:?}",
+                negotiated, min
+            )),
+        }
+    }
+    if let Some(allowed) = &config.allowed_ciphers {
+        let cipher = info.cipher.as_deref().unwrap_or("unknown");
+        if !allowed.iter().any(|c| c == cipher) {
+            failures.push(format!("cipher {} is not allowed", cipher));
+        }
+    }
+
+    failures
+}
+
+// ─── Public entry point ───────────────────────────────────────────────────────
+
+pub async fn check_tls(address: &str, config: &TlsCheck) -> TlsCheckResult {
+    let start_time = Instant::now();
+
+    let addr = address.to_string();
+    let cfg = config.clone();
+    let outcome = tokio::task::spawn_blocking(move || inspect_endpoint(&addr, &cfg))
+        .await
+        .unwrap_or_else(|e| Err(format!("task failed: {}", e)));
+    let response_time_ms = start_time.elapsed().as_millis();
+
+    match outcome {
+        Ok(info) => {
+            let failures = evaluate_thresholds(config, &info);
+            let status = if failures.is_empty() {
+                CheckStatus::Healthy
+            } else {
+                CheckStatus::Unhealthy(format!(
+                    "Certificate checks failed: {}",
+                    failures.join("; ")
+                ))
+            };
+            TlsCheckResult {
+                status,
+                response_time_ms,
+                cert_days_remaining: info.days_remaining,
+                cert_is_valid: Some(info.is_valid),
+                chain_valid: Some(info.chain_valid),
+                hostname_matches: Some(info.hostname_matches),
+                subject: info.subject,
+                issuer: info.issuer,
+                sans: info.sans,
+                protocol_version: info.protocol_version,
+                cipher: info.cipher,
+            }
+        }
+        Err(e) => TlsCheckResult {
+            status: CheckStatus::Unhealthy(format!("TLS check failed: {}", e)),
+            response_time_ms,
+            cert_days_remaining: None,
+            cert_is_valid: None,
+            chain_valid: None,
+            hostname_matches: None,
+            subject: None,
+            issuer: None,
+            sans: Vec::new(),
+            protocol_version: None,
+            cipher: None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AddressFamily;
+    use crate::monitoring::tls::self_signed_cert;
+    use openssl::ssl::{SslAcceptor, SslMethod};
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    /// Accept one connection, optionally play the server side of SMTP STARTTLS,
+    /// then complete a TLS handshake.
+    fn spawn_tls_server(smtp_starttls: bool) -> u16 {
+        let (cert, key) = self_signed_cert();
+        let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+        acceptor.set_certificate(&cert).unwrap();
+        acceptor.set_private_key(&key).unwrap();
+        let acceptor = acceptor.build();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            if smtp_starttls {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                stream.write_all(b"220 mail.test ESMTP\r\n").unwrap();
+                reader.read_line(&mut line).unwrap();
+                assert!(line.starts_with("EHLO"), "{}", line);
+                stream
+                    .write_all(b"250-mail.test\r\n250 STARTTLS\r\n")
+                    .unwrap();
+                line.clear();
+                reader.read_line(&mut line).unwrap();
+                assert_eq!(line, "STARTTLS\r\n");
+                stream.write_all(b"220 Ready to start TLS\r\n").unwrap();
+            }
+            if let Ok(mut tls) = acceptor.accept(stream) {
+                let mut buf = [0u8; 1];
+                let _ = tls.read(&mut buf);
+            }
+        });
+        port
+    }
+
+    fn tls_check(port: u16) -> TlsCheck {
+        TlsCheck {
+            name: None,
+            port,
+            timeout_seconds: 5,
+            starttls: None,
+            server_name: Some("localhost".to_string()),
+            min_days_remaining: None,
+            require_valid_chain: false,
+            require_hostname_match: true,
+            expected_issuer: None,
+            expected_subject: None,
+            required_sans: None,
+            min_protocol_version: None,
+            allowed_ciphers: None,
+            address_family: AddressFamily::Both,
+            tls: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tls_check_reports_certificate_details() {
+        let port = spawn_tls_server(false);
+        let mut config = tls_check(port);
+        config.min_days_remaining = Some(14);
+        config.expected_subject = Some("CN=localhost".to_string());
+        config.expected_issuer = Some("O=Ruptime Test".to_string());
+        config.required_sans = Some(vec!["localhost".to_string(), "127.0.0.1".to_string()]);
+        config.min_protocol_version = Some(TlsVersion::Tls12);
+
+        let result = check_tls("127.0.0.1", &config).await;
+        assert!(
+            matches!(result.status, CheckStatus::Healthy),
+            "{:?}",
+            result.status
+        );
+        assert!(matches!(result.cert_days_remaining, Some(29..=30)));
+        assert_eq!(result.hostname_matches, Some(true));
+        assert_eq!(result.chain_valid, Some(false));
+        assert_eq!(result.cert_is_valid, Some(false));
+        assert!(result.protocol_version.is_some());
+        assert!(result.cipher.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_tls_check_threshold_failures() {
+        let port = spawn_tls_server(false);
+        let mut config = tls_check(port);
+        config.server_name = Some("other.example.com".to_string());
+        config.require_valid_chain = true;
+        config.min_days_remaining = Some(60);
+
+        let result = check_tls("127.0.0.1", &config).await;
+        match result.status {
+            CheckStatus::Unhealthy(msg) => {
+                assert!(msg.contains("expires in"), "{}", msg);
+                assert!(msg.contains("chain is not trusted"), "{}", msg);
+                assert!(msg.contains("does not match the hostname"), "{}", msg);
+            }
+            CheckStatus::Healthy => panic!("expected threshold failures"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tls_check_smtp_starttls() {
+        let port = spawn_tls_server(true);
+        let mut config = tls_check(port);
+        config.starttls = Some(StartTlsProtocol::Smtp);
+
+        let result = check_tls("127.0.0.1", &config).await;
+        assert!(
+            matches!(result.status, CheckStatus::Healthy),
+            "{:?}",
+            result.status
+        );
+        assert_eq!(result.sans, vec!["localhost", "127.0.0.1"]);
+    }
+
+    #[tokio::test]
+    async fn test_tls_check_trusts_ca_file() {
+        let (cert, key) = self_signed_cert();
+        let dir = tempfile::tempdir().unwrap();
+        let ca_file = dir.path().join("ca.pem");
+        std::fs::write(&ca_file, cert.to_pem().unwrap()).unwrap();
+        let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+        acceptor.set_certificate(&cert).unwrap();
+        acceptor.set_private_key(&key).unwrap();
+        let acceptor = acceptor.build();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            if let Ok(mut tls) = acceptor.accept(stream) {
+                let mut buf = [0u8; 1];
+                let _ = tls.read(&mut buf);
+            }
+        });
+
+        let mut config = tls_check(port);
+        config.require_valid_chain = true;
+        config.tls = Some(TlsClientConfig {
+            ca_file: Some(ca_file.to_str().unwrap().to_string()),
+            ..Default::default()
+        });
+        let result = check_tls("127.0.0.1", &config).await;
+        assert!(
+            matches!(result.status, CheckStatus::Healthy),
+            "{:?}",
+            result.status
+        );
+        assert_eq!(result.chain_valid, Some(true));
+    }
+}
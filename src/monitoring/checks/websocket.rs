@@ -0,0 +1,560 @@
+use crate::config::{AuthConfig, WebSocketCheck, WebSocketProtocol};
+use crate::monitoring::assertions::evaluate_assertions_with_data;
+use crate::monitoring::auth::RequestAuth;
+use crate::monitoring::checks::http::{parse_headers, parse_status_code};
+use crate::monitoring::net::connect_blocking;
+use crate::monitoring::signing::{host_header, request_target};
+use crate::monitoring::tls::{connect_tls, MaybeTlsStream, TlsCertInfo};
+use crate::monitoring::types::{CheckStatus, WebSocketCheckResult};
+use base64::Engine as _;
+use std::io::{Read, Write};
+use std::time::{Duration, Instant};
+
+/// Fixed GUID from RFC 6455 used to derive `Sec-WebSocket-Accept`.
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+const MAX_HANDSHAKE_SIZE: usize = 16 * 1024;
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+fn expected_accept_key(key: &str) -> String {
+    let digest = openssl::sha::sha1(format!("{}{}", key, WEBSOCKET_GUID).as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(digest)
+}
+
+// ─── Framed connection ────────────────────────────────────────────────────────
+
+struct WebSocketConnection {
+    stream: MaybeTlsStream,
+    buffer: Vec<u8>,
+}
+
+impl WebSocketConnection {
+    /// Read from the socket until at least `n` bytes are buffered.
+    fn fill(&mut self, n: usize) -> Result<(), String> {
+        let mut tmp = [0u8; 16 * 1024];
+        while self.buffer.len() < n {
+            let read = self
+                .stream
+                .read(&mut tmp)
+                .map_err(|e| format!("Read failed: {}", e))?;
+            if read == 0 {
+                return Err("Connection closed by server".into());
+            }
+            self.buffer.extend_from_slice(&tmp[..read]);
+        }
+        Ok(())
+    }
+
+    /// Read the HTTP upgrade response, leaving any frame bytes that followed it buffered.
+    fn read_handshake_response(&mut self) -> Result<Vec<u8>, String> {
+        loop {
+            if let Some(pos) = self.buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+                let head: Vec<u8> = self.buffer.drain(..pos + 4).collect();
+                return Ok(head);
+            }
+            if self.buffer.len() > MAX_HANDSHAKE_SIZE {
+                return Err("Handshake response headers too large".into());
+            }
+            let wanted = self.buffer.len() + 1;
+            self.fill(wanted)?;
+        }
+    }
+
+    /// Write a single masked client frame.
+    fn write_frame(&mut self, opcode: u8, payload: &[u8]) -> Result<(), String> {
+        let mut frame = Vec::with_capacity(payload.len() + 14);
+        frame.push(0x80 | opcode);
+        match payload.len() {
+            len if len < 126 => frame.push(0x80 | len as u8),
+            len if len <= u16::MAX as usize => {
+                frame.push(0x80 | 126);
+                frame.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+            len => {
+                frame.push(0x80 | 127);
+                frame.extend_from_slice(&(len as u64).to_be_bytes());
+            }
+        }
+        let mut mask = [0u8; 4];
+        mask.copy_from_slice(&uuid::Uuid::new_v4().as_bytes()[..4]);
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+
+        self.stream
+            .write_all(&frame)
+            .and_then(|_| self.stream.flush())
+            .map_err(|e| format!("Write failed: {}", e))
+    }
+
+    /// Read one frame, returning (fin, opcode, unmasked payload).
+    fn read_frame(&mut self) -> Result<(bool, u8, Vec<u8>), String> {
+        self.fill(2)?;
+        let fin = self.buffer[0] & 0x80 != 0;
+        let opcode = self.buffer[0] & 0x0F;
+        let masked = self.buffer[1] & 0x80 != 0;
+
+        let (header_len, payload_len) = match self.buffer[1] & 0x7F {
+            126 => {
+                self.fill(4)?;
+                (
+                    4,
+                    u16::from_be_bytes([self.buffer[2], self.buffer[3]]) as u64,
+                )
+            }
+            127 => {
+                self.fill(10)?;
+                let mut len = [0u8; 8];
+                len.copy_from_slice(&self.buffer[2..10]);
+                (10, u64::from_be_bytes(len))
+            }
+            len => (2, len as u64),
+        };
+        if payload_len > MAX_MESSAGE_SIZE as u64 {
+            return Err(format!("Frame of {} bytes exceeds limit", payload_len));
+        }
+
+        let mask_len = if masked { 4 } else { 0 };
+        let frame_len = header_len + mask_len + payload_len as usize;
+        self.fill(frame_len)?;
+        let frame: Vec<u8> = self.buffer.drain(..frame_len).collect();
+
+        let mut payload = frame[header_len + mask_len..].to_vec();
+        if masked {
+            let key = &frame[header_len..header_len + 4];
+            for (i, b) in payload.iter_mut().enumerate() {
+                *b ^= key[i % 4];
+            }
+        }
+        Ok((fin, opcode, payload))
+    }
+
+    /// Read the next complete data message, answering pings along the way.
+    fn read_message(&mut self) -> Result<Vec<u8>, String> {
+        let mut message = Vec::new();
+        loop {
+            let (fin, opcode, payload) = self.read_frame()?;
+            match opcode {
+                OPCODE_CONTINUATION | OPCODE_TEXT | OPCODE_BINARY => {
+                    message.extend_from_slice(&payload);
+                    if message.len() > MAX_MESSAGE_SIZE {
+                        return Err("Message exceeds size limit".into());
+                    }
+                    if fin {
+                        return Ok(message);
+                    }
+                }
+                OPCODE_PING => self.write_frame(OPCODE_PONG, &payload)?,
+                OPCODE_PONG => {}
+                OPCODE_CLOSE => {
+                    return Err(match payload.get(..2) {
+                        Some(code) => format!(
+                            "Server closed the connection (code {})",
+                            u16::from_be_bytes([code[0], code[1]])
+                        ),
+                        None => "Server closed the connection".into(),
+                    })
+                }
+                other => return Err(format!("Unexpected frame opcode: {:#x}", other)),
+            }
+        }
+    }
+}
+
+// ─── Session ──────────────────────────────────────────────────────────────────
+
+struct SessionOutcome {
+    handshake_time_ms: u128,
+    message_rtt_ms: Option<u128>,
+    headers: http::HeaderMap,
+    first_message: Option<String>,
+}
+
+fn run_websocket_session(
+    address: &str,
+    config: &WebSocketCheck,
+    extra_headers: &[(String, String)],
+) -> (Result<SessionOutcome, String>, Option<TlsCertInfo>) {
+    let timeout = Duration::from_secs(config.timeout_seconds);
+    let mut cert = None;
+
+    let outcome = (|| {
+        let tcp = connect_blocking(address, config.port, config.address_family, timeout)?;
+        tcp.set_read_timeout(Some(timeout))
+            .map_err(|e| format!("set_read_timeout: {}", e))?;
+        tcp.set_write_timeout(Some(timeout))
+            .map_err(|e| format!("set_write_timeout: {}", e))?;
+        tcp.set_nodelay(true).ok();
+
+        let stream = match config.protocol {
+            WebSocketProtocol::Ws => MaybeTlsStream::Plain(tcp),
+            WebSocketProtocol::Wss => {
+                let options = config.tls.clone().unwrap_or_default();
+                let (tls, info) = connect_tls(address, tcp, &options, options.verify_certificate)?;
+                cert = Some(info);
+                MaybeTlsStream::Tls(Box::new(tls))
+            }
+        };
+        let mut conn = WebSocketConnection {
+            stream,
+            buffer: Vec::with_capacity(4096),
+        };
+
+        // 1. Upgrade handshake
+        let key = base64::engine::general_purpose::STANDARD.encode(uuid::Uuid::new_v4().as_bytes());
+        let mut request = format!(
+            "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: ruptime/1.0\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n",
+            config.path,
+            host_header(&websocket_url(address, config)?),
+            key
+        );
+        for (k, v) in extra_headers {
+            request.push_str(&format!("{}: {}\r\n", k, v));
+        }
+        request.push_str("\r\n");
+        let request_sent = Instant::now();
+        conn.stream
+            .write_all(request.as_bytes())
+            .map_err(|e| format!("Write failed: {}", e))?;
+
+        let head = conn.read_handshake_response()?;
+        let status_code = parse_status_code(&head);
+        let headers = parse_headers(&head);
+        if status_code != 101 {
+            return Err(format!("Handshake rejected with status {}", status_code));
+        }
+        let upgrade_ok = headers
+            .get("upgrade")
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.eq_ignore_ascii_case("websocket"));
+        if !upgrade_ok {
+            return Err("Handshake response is missing 'Upgrade: websocket'".into());
+        }
+        let accept = headers
+            .get("sec-websocket-accept")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("");
+        if accept != expected_accept_key(&key) {
+            return Err("Invalid Sec-WebSocket-Accept in handshake response".into());
+        }
+        let handshake_time_ms = request_sent.elapsed().as_millis();
+
+        // 2. Optional message exchange
+        let mut message_rtt_ms = None;
+        let mut first_message = None;
+        if config.send_message.is_some() || config.assertions.is_some() {
+            let sent_at = Instant::now();
+            if let Some(message) = &config.send_message {
+                conn.write_frame(OPCODE_TEXT, message.as_bytes())?;
+            }
+            let received = conn.read_message()?;
+            if config.send_message.is_some() {
+                message_rtt_ms = Some(sent_at.elapsed().as_millis());
+            }
+            first_message = Some(String::from_utf8_lossy(&received).into_owned());
+        }
+
+        // Normal closure; the server's close reply is not awaited.
+        let _ = conn.write_frame(OPCODE_CLOSE, &1000u16.to_be_bytes());
+
+        Ok(SessionOutcome {
+            handshake_time_ms,
+            message_rtt_ms,
+            headers,
+            first_message,
+        })
+    })();
+
+    (outcome, cert)
+}
+
+/// The URL of the check, with IPv6 literals in brackets.
+fn websocket_url(address: &str, config: &WebSocketCheck) -> Result<url::Url, String> {
+    let scheme = match config.protocol {
+        WebSocketProtocol::Ws => "ws",
+        WebSocketProtocol::Wss => "wss",
+    };
+    let host = if address.contains(':') {
+        format!("[{}]", address)
+    } else {
+        address.to_string()
+    };
+    url::Url::parse(&format!(
+        "{}://{}:{}{}",
+        scheme, host, config.port, config.path
+    ))
+    .map_err(|e| format!("Invalid URL: {}", e))
+}
+
+/// Authenticate the upgrade request: a query API key goes into `config.path`,
+/// everything else into `headers`. Digest needs a second handshake and is not
+/// supported.
+async fn handshake_auth(
+    address: &str,
+    config: &mut WebSocketCheck,
+    auth: &AuthConfig,
+    headers: &mut Vec<(String, String)>,
+) -> Result<(), String> {
+    if matches!(auth, AuthConfig::Digest { .. }) {
+        return Err("Digest authentication is not supported for WebSocket checks".to_string());
+    }
+    let mut url = websocket_url(address, config)?;
+    let mut request_auth = RequestAuth::new(auth, None).await?;
+    request_auth.apply_to_url(&mut url);
+    config.path = request_target(&url);
+    headers.extend(request_auth.headers("GET", &url, b"")?);
+    Ok(())
+}
+
+// ─── Public entry point ───────────────────────────────────────────────────────
+
+pub async fn check_websocket(address: &str, config: &WebSocketCheck) -> WebSocketCheckResult {
+    let start_time = Instant::now();
+
+    let mut extra_headers: Vec<(String, String)> = Vec::new();
+    if let Some(headers) = &config.headers {
+        for (k, v) in headers {
+            extra_headers.push((k.clone(), v.clone()));
+        }
+    }
+    let mut cfg = config.clone();
+    if let Some(auth) = &config.auth {
+        if let Err(e) = handshake_auth(address, &mut cfg, auth, &mut extra_headers).await {
+            return WebSocketCheckResult {
+                status: CheckStatus::Unhealthy(e),
+                response_time_ms: start_time.elapsed().as_millis(),
+                handshake_time_ms: 0,
+                message_rtt_ms: None,
+                cert_days_remaining: None,
+                cert_is_valid: None,
+                assertions: Vec::new(),
+            };
+        }
+    }
+
+    let addr = address.to_string();
+    let result =
+        tokio::task::spawn_blocking(move || run_websocket_session(&addr, &cfg, &extra_headers))
+            .await;
+
+    let (outcome, cert) = match result {
+        Ok(r) => r,
+        Err(e) => (Err(format!("task failed: {}", e)), None),
+    };
+    let (cert_days_remaining, cert_is_valid) = match &cert {
+        Some(c) => (c.days_remaining, Some(c.is_valid)),
+        None => (None, None),
+    };
+    let response_time_ms = start_time.elapsed().as_millis();
+
+    let outcome = match outcome {
+        Ok(o) => o,
+        Err(e) => {
+            return WebSocketCheckResult {
+                status: CheckStatus::Unhealthy(format!("WebSocket check failed: {}", e)),
+                response_time_ms,
+                handshake_time_ms: 0,
+                message_rtt_ms: None,
+                cert_days_remaining,
+                cert_is_valid,
+                assertions: Vec::new(),
+            }
+        }
+    };
+
+    let mut status = CheckStatus::Healthy;
+    let mut assertion_results = Vec::new();
+    if let (Some(assertions), Some(message)) = (&config.assertions, &outcome.first_message) {
+        // `Duration` assertions measure the message round trip when a message
+        // was sent, otherwise the handshake.
+        let duration_ms = outcome.message_rtt_ms.unwrap_or(outcome.handshake_time_ms);
+        assertion_results = evaluate_assertions_with_data(
+            assertions,
+            http::StatusCode::SWITCHING_PROTOCOLS,
+            &outcome.headers,
+            message,
+            duration_ms,
+            cert.as_ref(),
+            &[],
+        );
+
+        let failed: Vec<_> = assertion_results.iter().filter(|r| !r.passed).collect();
+        if !failed.is_empty() {
+            let msgs: Vec<String> = failed.iter().map(|r| r.message.clone()).collect();
+            status = CheckStatus::Unhealthy(format!("Assertion failures: {}", msgs.join("; ")));
+        }
+    }
+
+    WebSocketCheckResult {
+        status,
+        response_time_ms,
+        handshake_time_ms: outcome.handshake_time_ms,
+        message_rtt_ms: outcome.message_rtt_ms,
+        cert_days_remaining,
+        cert_is_valid,
+        assertions: assertion_results,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{
+        AddressFamily, AssertionPredicate, AssertionQuery, AssertionValue, HttpAssertion,
+    };
+    use std::net::TcpListener;
+
+    /// Accept one connection, complete the handshake with `status_line` and echo
+    /// the first client frame back as an unmasked text frame.
+    fn spawn_echo_server(status_line: &'static str, corrupt_accept: bool) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut conn = WebSocketConnection {
+                stream: MaybeTlsStream::Plain(stream),
+                buffer: Vec::new(),
+            };
+            let head = conn.read_handshake_response().unwrap();
+            let head = String::from_utf8_lossy(&head).into_owned();
+            let key = head
+                .lines()
+                .find_map(|l| l.strip_prefix("Sec-WebSocket-Key: "))
+                .unwrap()
+                .trim()
+                .to_string();
+            assert!(head.contains("Authorization: Bearer ws-token"));
+            let accept = if corrupt_accept {
+                "bogus".to_string()
+            } else {
+                expected_accept_key(&key)
+            };
+            let response = format!(
+                "{}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                status_line, accept
+            );
+            conn.stream.write_all(response.as_bytes()).unwrap();
+            if let Ok((_, _, payload)) = conn.read_frame() {
+                let mut frame = vec![0x80 | OPCODE_TEXT, payload.len() as u8];
+                frame.extend_from_slice(&payload);
+                conn.stream.write_all(&frame).unwrap();
+            }
+        });
+        port
+    }
+
+    fn websocket_check(port: u16) -> WebSocketCheck {
+        WebSocketCheck {
+            name: None,
+            port,
+            path: "/realtime".to_string(),
+            protocol: WebSocketProtocol::Ws,
+            timeout_seconds: 5,
+            auth: Some(crate::config::AuthConfig::Bearer {
+                token: "ws-token".into(),
+            }),
+            headers: None,
+            send_message: Some(r#"{"type":"ping","ok":true}"#.to_string()),
+            assertions: None,
+            address_family: AddressFamily::Both,
+            tls: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_websocket_echo_with_jsonpath_assertion() {
+        let port = spawn_echo_server("HTTP/1.1 101 Switching Protocols", false);
+        let mut config = websocket_check(port);
+        config.assertions = Some(vec![
+            HttpAssertion {
+                query: AssertionQuery::JsonPath {
+                    path: "$.type".to_string(),
+                },
+                predicate: AssertionPredicate::Equals,
+                value: AssertionValue::String("ping".to_string()),
+            },
+            HttpAssertion {
+                query: AssertionQuery::Status,
+                predicate: AssertionPredicate::Equals,
+                value: AssertionValue::Integer(101),
+            },
+        ]);
+
+        let result = check_websocket("127.0.0.1", &config).await;
+        assert!(
+            matches!(result.status, CheckStatus::Healthy),
+            "{:?}",
+            result.status
+        );
+        assert!(result.message_rtt_ms.is_some());
+        assert_eq!(result.cert_is_valid, None);
+    }
+
+    #[tokio::test]
+    async fn test_websocket_failing_assertion_on_first_frame() {
+        let port = spawn_echo_server("HTTP/1.1 101 Switching Protocols", false);
+        let mut config = websocket_check(port);
+        config.assertions = Some(vec![HttpAssertion {
+            query: AssertionQuery::JsonPath {
+                path: "$.ok".to_string(),
+            },
+            predicate: AssertionPredicate::Equals,
+            value: AssertionValue::Boolean(false),
+        }]);
+
+        let result = check_websocket("127.0.0.1", &config).await;
+        match result.status {
+            CheckStatus::Unhealthy(msg) => assert!(msg.contains("Assertion failures"), "{}", msg),
+            CheckStatus::Healthy => panic!("expected assertion failure"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_websocket_rejected_handshake() {
+        let port = spawn_echo_server("HTTP/1.1 401 Unauthorized", false);
+        let result = check_websocket("127.0.0.1", &websocket_check(port)).await;
+        match result.status {
+            CheckStatus::Unhealthy(msg) => assert!(msg.contains("status 401"), "{}", msg),
+            CheckStatus::Healthy => panic!("expected handshake rejection"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_websocket_invalid_accept_key() {
+        let port = spawn_echo_server("HTTP/1.1 101 Switching Protocols", true);
+        let result = check_websocket("127.0.0.1", &websocket_check(port)).await;
+        match result.status {
+            CheckStatus::Unhealthy(msg) => assert!(msg.contains("Sec-WebSocket-Accept"), "{}", msg),
+            CheckStatus::Healthy => panic!("expected accept key failure"),
+        }
+    }
+
+    #[test]
+    fn test_websocket_host_header() {
+        let mut config = websocket_check(8080);
+        assert_eq!(
+            host_header(&websocket_url("2001:db8::1", &config).unwrap()),
+            "[2001:db8::1]:8080"
+        );
+        config.port = 443;
+        config.protocol = WebSocketProtocol::Wss;
+        assert_eq!(
+            host_header(&websocket_url("ws.example.com", &config).unwrap()),
+            "ws.example.com"
+        );
+    }
+
+    #[test]
+    fn test_expected_accept_key_rfc_example() {
+        // Example handshake from RFC 6455, section 1.3
+        assert_eq!(
+            expected_accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use crate::config::{AddressFamily, ProxyConfig, TlsClientConfig};
+use crate::monitoring::net;
+use crate::monitoring::proxy::{self, Transport};
+use crate::monitoring::tls::{connect_tls_async, TlsCertInfo};
+use hyper::client::connect::{Connected, Connection};
+use hyper::Uri;
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_openssl::SslStream;
+
+// ─── Pooled HTTP/1.1 client ───────────────────────────────────────────────────
+//
+// hyper's client with a connector that performs DNS resolution, the TCP
+// connect and the TLS handshake itself so each phase can be timed. The timings
+// and the peer certificate travel with the pooled connection and are attached
+// to every response served over it.
+
+/// How a pooled connection was established.
+#[derive(Debug, Clone)]
+pub struct ConnectionInfo {
+    pub established_at: Instant,
+    pub remote_addr: SocketAddr, // The proxy's address when connected through one
+    pub dns: Duration,
+    pub connect: Duration,
+    pub tls: Option<Duration>,
+    pub certificate: Option<TlsCertInfo>,
+    /// Plain HTTP sent to a proxy, which needs requests in absolute form.
+    pub forwarded: bool,
+}
+
+pub type HttpClient = hyper::Client<TimedConnector, hyper::Body>;
+
+/// Connection settings that need a client (and pool) of their own.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ClientOptions {
+    pub family: AddressFamily,
+    /// Connect to this address instead of resolving the host, for requests to that host.
+    pub pinned: Option<(String, IpAddr)>,
+    pub tls: TlsClientConfig,
+    /// Refuse peers whose certificate chain or hostname does not verify.
+    pub verify: bool,
+    pub proxy: Option<ProxyConfig>,
+}
+
+pub fn build_client(options: ClientOptions) -> HttpClient {
+    hyper::Client::builder().build(TimedConnector {
+        options: Arc::new(options),
+    })
+}
+
+// ─── Connector ────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone)]
+pub struct TimedConnector {
+    options: Arc<ClientOptions>,
+}
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+impl tower_service::Service<Uri> for TimedConnector {
+    type Response = TimedStream;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<TimedStream, BoxError>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let options = Arc::clone(&self.options);
+        Box::pin(async move { connect(uri, &options).await.map_err(BoxError::from) })
+    }
+}
+
+async fn connect(uri: Uri, options: &ClientOptions) -> Result<TimedStream, String> {
+    let is_https = uri.scheme_str() == Some("https");
+    let host = uri
+        .host()
+        .ok_or_else(|| format!("URL {} has no host", uri))?
+        .trim_matches(|c| c == '[' || c == ']')
+        .to_string();
+    let port = uri.port_u16().unwrap_or(if is_https { 443 } else { 80 });
+
+    let proxy = proxy::select(options.proxy.as_ref(), &host, port)?;
+    let (connect_host, connect_port) = match &proxy {
+        Some(proxy) => (proxy.host.as_str(), proxy.port),
+        None => (host.as_str(), port),
+    };
+
+    let start = Instant::now();
+    let addrs = match &options.pinned {
+        Some((pinned_host, ip)) if proxy.is_none() && *pinned_host == host => {
+            vec![SocketAddr::new(*ip, port)]
+        }
+        _ => net::resolve(connect_host, connect_port, options.family).await?,
+    };
+    let dns = start.elapsed();
+
+    // Connect time includes setting up the tunnel through a proxy
+    let connect_start = Instant::now();
+    let tcp = net::connect(&addrs).await?;
+    let remote_addr = tcp.peer_addr().map_err(|e| e.to_string())?;
+    let _ = tcp.set_nodelay(true);
+    let transport = match &proxy {
+        Some(proxy) => proxy.establish(tcp, &host, port, is_https).await?,
+        None => Transport::Tcp(tcp),
+    };
+    let connect = connect_start.elapsed();
+
+    if !is_https {
+        let info = ConnectionInfo {
+            established_at: Instant::now(),
+            remote_addr,
+            dns,
+            connect,
+            tls: None,
+            certificate: None,
+            forwarded: proxy.as_ref().is_some_and(|p| p.forwards(false)),
+        };
+        return Ok(TimedStream::Plain(transport, Arc::new(info)));
+    }
+
+    let (stream, handshake) =
+        connect_tls_async(&host, transport, &options.tls, options.verify).await?;
+    let info = ConnectionInfo {
+        established_at: Instant::now(),
+        remote_addr,
+        dns,
+        connect,
+        tls: Some(handshake.duration),
+        certificate: Some(handshake.certificate),
+        forwarded: false,
+    };
+    Ok(TimedStream::Tls(Box::new(stream), Arc::new(info)))
+}
+
+// ─── Connection ───────────────────────────────────────────────────────────────
+
+pub enum TimedStream {
+    Plain(Transport, Arc<ConnectionInfo>),
+    Tls(Box<SslStream<Transport>>, Arc<ConnectionInfo>),
+}
+
+impl Connection for TimedStream {
+    fn connected(&self) -> Connected {
+        let info = match self {
+            TimedStream::Plain(_, info) | TimedStream::Tls(_, info) => info,
+        };
+        Connected::new()
+            .proxy(info.forwarded)
+            .extra(Arc::clone(info))
+    }
+}
+
+impl AsyncRead for TimedStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            TimedStream::Plain(s, _) => Pin::new(s).poll_read(cx, buf),
+            TimedStream::Tls(s, _) => Pin::new(s.as_mut()).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for TimedStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            TimedStream::Plain(s, _) => Pin::new(s).poll_write(cx, buf),
+            TimedStream::Tls(s, _) => Pin::new(s.as_mut()).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            TimedStream::Plain(s, _) => Pin::new(s).poll_flush(cx),
+            TimedStream::Tls(s, _) => Pin::new(s.as_mut()).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            TimedStream::Plain(s, _) => Pin::new(s).poll_shutdown(cx),
+            TimedStream::Tls(s, _) => Pin::new(s.as_mut()).poll_shutdown(cx),
+        }
+    }
+}
@@ -1,12 +1,18 @@
 pub mod assertions;
 pub mod auth;
 pub mod checks;
+pub mod http_client;
 pub mod monitoring_loop;
+pub mod net;
+pub mod proxy;
+pub mod signing;
+pub mod tls;
 pub mod types;
+pub mod xpath;
 
 // Re-export main types and functions for backwards compatibility
 pub use assertions::*;
 pub use auth::*;
 pub use checks::*;
-pub use monitoring_loop::run_monitoring_loop;
+pub use monitoring_loop::{run_check, run_monitoring_loop};
 pub use types::*;
@@ -0,0 +1,227 @@
+use crate::config::AddressFamily;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::task::JoinSet;
+
+// ─── Name resolution ──────────────────────────────────────────────────────────
+
+/// Keep the addresses `family` allows, in the order connections should be tried.
+/// Happy Eyeballs interleaves the families, IPv6 first (RFC 8305 §4).
+pub fn order_addresses(
+    addrs: Vec<SocketAddr>,
+    family: AddressFamily,
+    host: &str,
+) -> Result<Vec<SocketAddr>, String> {
+    let ordered: Vec<SocketAddr> = match family {
+        AddressFamily::Both => addrs,
+        AddressFamily::V4 => addrs.into_iter().filter(SocketAddr::is_ipv4).collect(),
+        AddressFamily::V6 => addrs.into_iter().filter(SocketAddr::is_ipv6).collect(),
+        AddressFamily::HappyEyeballs => {
+            let (v6, v4): (Vec<_>, Vec<_>) = addrs.into_iter().partition(SocketAddr::is_ipv6);
+            let mut interleaved = Vec::with_capacity(v6.len() + v4.len());
+            let (mut v6, mut v4) = (v6.into_iter(), v4.into_iter());
+            loop {
+                match (v6.next(), v4.next()) {
+                    (None, None) => break,
+                    (a, b) => interleaved.extend(a.into_iter().chain(b)),
+                }
+            }
+            interleaved
+        }
+    };
+    if ordered.is_empty() {
+        return Err(match family {
+            AddressFamily::V4 => format!("No IPv4 addresses resolved for {}", host),
+            AddressFamily::V6 => format!("No IPv6 addresses resolved for {}", host),
+            _ => format!("No addresses resolved for {}", host),
+        });
+    }
+    Ok(ordered)
+}
+
+pub async fn resolve(
+    host: &str,
+    port: u16,
+    family: AddressFamily,
+) -> Result<Vec<SocketAddr>, String> {
+    let host = host.trim_matches(|c| c == '[' || c == ']');
+    let addrs = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| format!("DNS lookup for {} failed: {}", host, e))?
+        .collect();
+    order_addresses(addrs, family, host)
+}
+
+fn resolve_blocking(
+    host: &str,
+    port: u16,
+    family: AddressFamily,
+) -> Result<Vec<SocketAddr>, String> {
+    let host = host.trim_matches(|c| c == '[' || c == ']');
+    let addrs = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| format!("DNS lookup failed: {}", e))?
+        .collect();
+    order_addresses(addrs, family, host)
+}
+
+// ─── Connecting ───────────────────────────────────────────────────────────────
+
+/// Blocking connect for the line and frame protocol probes. The addresses
+/// `family` allows are tried in turn within `timeout`, which is shared out so
+/// an unreachable address cannot use it all up.
+pub fn connect_blocking(
+    host: &str,
+    port: u16,
+    family: AddressFamily,
+    timeout: Duration,
+) -> Result<TcpStream, String> {
+    connect_each_blocking(&resolve_blocking(host, port, family)?, timeout)
+}
+
+fn connect_each_blocking(addrs: &[SocketAddr], timeout: Duration) -> Result<TcpStream, String> {
+    let deadline = Instant::now() + timeout;
+    let mut last_error = String::from("No addresses to connect to");
+    for (i, addr) in addrs.iter().enumerate() {
+        // Each attempt gets an equal part of the time that is left
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let attempt = remaining / (addrs.len() - i) as u32;
+        if attempt.is_zero() {
+            return Err(format!("TCP connect to {} timed out", addr));
+        }
+        match TcpStream::connect_timeout(addr, attempt) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = format!("TCP connect to {} failed: {}", addr, e),
+        }
+    }
+    Err(last_error)
+}
+
+/// Delay before the next connection attempt starts alongside a pending one.
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Connect to the first address, in the given order, that accepts. Attempts
+/// are staggered and raced as in Happy Eyeballs, so an unreachable address
+/// delays the next one by `CONNECTION_ATTEMPT_DELAY` at most.
+pub async fn connect(addrs: &[SocketAddr]) -> Result<tokio::net::TcpStream, String> {
+    let mut pending = addrs.iter().copied();
+    let mut attempts = JoinSet::new();
+    let mut last_error = String::from("No addresses to connect to");
+    loop {
+        if let Some(addr) = pending.next() {
+            attempts.spawn(async move { (addr, tokio::net::TcpStream::connect(addr).await) });
+        } else if attempts.is_empty() {
+            return Err(last_error);
+        }
+
+        // A failed attempt starts the next one right away, a slow one after the delay.
+        tokio::select! {
+            Some(joined) = attempts.join_next() => match joined {
+                Ok((_, Ok(stream))) => return Ok(stream), // Dropping the set aborts the rest
+                Ok((addr, Err(e))) => last_error = format!("TCP connect to {} failed: {}", addr, e),
+                Err(e) => last_error = format!("Connection attempt failed: {}", e),
+            },
+            _ = tokio::time::sleep(CONNECTION_ATTEMPT_DELAY), if pending.len() > 0 => {}
+        }
+    }
+}
+
+// ─── Request/response exchange ───────────────────────────────────────────────
+
+/// Send `request` and read until the server closes the connection. Read errors
+/// are reported rather than taken for the end of the response.
+pub async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    request: &str,
+) -> Result<Vec<u8>, String> {
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .map_err(|e| format!("Write failed: {}", e))?;
+    let mut response = Vec::with_capacity(16 * 1024);
+    stream
+        .read_to_end(&mut response)
+        .await
+        .map_err(|e| format!("Read failed: {}", e))?;
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(s: &str) -> SocketAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_order_addresses_by_family() {
+        let resolved = vec![
+            addr("192.0.2.1:80"),
+            addr("192.0.2.2:80"),
+            addr("[2001:db8::1]:80"),
+        ];
+        assert_eq!(
+            order_addresses(resolved.clone(), AddressFamily::V4, "h").unwrap(),
+            vec![addr("192.0.2.1:80"), addr("192.0.2.2:80")]
+        );
+        assert_eq!(
+            order_addresses(resolved.clone(), AddressFamily::HappyEyeballs, "h").unwrap(),
+            vec![
+                addr("[2001:db8::1]:80"),
+                addr("192.0.2.1:80"),
+                addr("192.0.2.2:80")
+            ]
+        );
+        assert_eq!(
+            order_addresses(resolved[..2].to_vec(), AddressFamily::V6, "h").unwrap_err(),
+            "No IPv6 addresses resolved for h"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_connect_skips_dead_addresses() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let live = listener.local_addr().unwrap();
+        let dead = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let stream = connect(&[dead, live]).await.unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), live);
+        assert!(connect(&[dead]).await.is_err());
+    }
+
+    // Nothing answers on TEST-NET-1, so a connection attempt hangs (or fails
+    // at once without a route)
+    const UNREACHABLE: &str = "192.0.2.1:9";
+
+    #[tokio::test]
+    async fn test_connect_does_not_wait_for_unreachable_address() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let live = listener.local_addr().unwrap();
+
+        let start = Instant::now();
+        let stream =
+            tokio::time::timeout(Duration::from_secs(5), connect(&[addr(UNREACHABLE), live]))
+                .await
+                .expect("stuck on the unreachable address")
+                .unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), live);
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_connect_blocking_shares_timeout_between_addresses() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let live = listener.local_addr().unwrap();
+
+        let start = Instant::now();
+        let stream =
+            connect_each_blocking(&[addr(UNREACHABLE), live], Duration::from_secs(4)).unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), live);
+        assert!(start.elapsed() < Duration::from_secs(3));
+    }
+}
@@ -0,0 +1,472 @@
+use crate::config::{AwsSigV4Config, HmacAlgorithm, HmacConfig, SignatureEncoding};
+use chrono::{DateTime, Utc};
+use openssl::hash::{hash, MessageDigest};
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn digest_hex(digest: MessageDigest, data: &[u8]) -> Result<String, String> {
+    hash(digest, data)
+        .map(|d| hex(&d))
+        .map_err(|e| format!("Hashing failed: {}", e))
+}
+
+fn hmac(digest: MessageDigest, key: &[u8], data: &[u8]) -> Result<Vec<u8>, String> {
+    let key = PKey::hmac(key).map_err(|e| format!("Invalid HMAC key: {}", e))?;
+    let mut signer =
+        Signer::new(digest, &key).map_err(|e| format!("HMAC signing failed: {}", e))?;
+    signer
+        .update(data)
+        .and_then(|_| signer.sign_to_vec())
+        .map_err(|e| format!("HMAC signing failed: {}", e))
+}
+
+/// Path and query as they appear in the request line.
+pub fn request_target(url: &url::Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    }
+}
+
+/// The `Host` header hyper sends for `url`: the port only when not the default.
+pub fn host_header(url: &url::Url) -> String {
+    let host = url.host_str().unwrap_or("");
+    match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    }
+}
+
+// ─── HTTP Digest (RFC 7616) ───────────────────────────────────────────────────
+
+/// The parameters of a `WWW-Authenticate: Digest` challenge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DigestChallenge {
+    pub realm: String,
+    pub nonce: String,
+    pub opaque: Option<String>,
+    pub algorithm: Option<String>,
+    pub qop: Option<String>,
+}
+
+/// Find the Digest challenge in a `WWW-Authenticate` value, which may list
+/// other schemes too.
+pub fn parse_digest_challenge(header: &str) -> Option<DigestChallenge> {
+    let start = header.to_ascii_lowercase().find("digest ")?;
+    let mut rest = &header[start + 7..];
+    let mut params = std::collections::HashMap::new();
+    loop {
+        rest = rest.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        let Some(eq) = rest.find('=') else { break };
+        let name = rest[..eq].trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            break; // The next scheme
+        }
+        rest = &rest[eq + 1..];
+        let value = if let Some(quoted) = rest.strip_prefix('"') {
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+            let mut end = quoted.len();
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '\\' => value.extend(chars.next().map(|(_, c)| c)),
+                    '"' => {
+                        end = i + 1;
+                        break;
+                    }
+                    c => value.push(c),
+                }
+            }
+            rest = &quoted[end..];
+            value
+        } else {
+            let end = rest.find(',').unwrap_or(rest.len());
+            let value = rest[..end].trim().to_string();
+            rest = &rest[end..];
+            value
+        };
+        params.insert(name.to_ascii_lowercase(), value);
+    }
+
+    Some(DigestChallenge {
+        realm: params.remove("realm").unwrap_or_default(),
+        nonce: params.remove("nonce")?,
+        opaque: params.remove("opaque"),
+        algorithm: params.remove("algorithm"),
+        qop: params.remove("qop"),
+    })
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The `Authorization` value answering `challenge` for one request.
+/// `nonce_count` counts the requests made with this nonce, from 1.
+#[allow(clippy::too_many_arguments)]
+pub fn digest_authorization(
+    username: &str,
+    password: &str,
+    challenge: &DigestChallenge,
+    method: &str,
+    uri: &str,
+    body: &[u8],
+    nonce_count: u32,
+    cnonce: &str,
+) -> Result<String, String> {
+    let algorithm = challenge.algorithm.as_deref().unwrap_or("MD5");
+    let (digest, session) = match algorithm.to_ascii_uppercase().as_str() {
+        "MD5" => (MessageDigest::md5(), false),
+        "MD5-SESS" => (MessageDigest::md5(), true),
+        "SHA-256" => (MessageDigest::sha256(), false),
+        "SHA-256-SESS" => (MessageDigest::sha256(), true),
+        _ => return Err(format!("Unsupported Digest algorithm '{}'", algorithm)),
+    };
+    let h = |data: String| digest_hex(digest, data.as_bytes());
+
+    let qop = match &challenge.qop {
+        None => None,
+        Some(offered) => {
+            let offered: Vec<&str> = offered.split(',').map(str::trim).collect();
+            if offered.contains(&"auth") {
+                Some("auth")
+            } else if offered.contains(&"auth-int") {
+                Some("auth-int")
+            } else {
+                return Err(format!("Unsupported Digest qop '{}'", offered.join(",")));
+            }
+        }
+    };
+    let nc = format!("{:08x}", nonce_count);
+
+    let mut ha1 = h(format!("{}:{}:{}", username, challenge.realm, password))?;
+    if session {
+        ha1 = h(format!("{}:{}:{}", ha1, challenge.nonce, cnonce))?;
+    }
+    let ha2 = match qop {
+        Some("auth-int") => h(format!("{}:{}:{}", method, uri, digest_hex(digest, body)?))?,
+        _ => h(format!("{}:{}", method, uri))?,
+    };
+    let response = match qop {
+        Some(qop) => h(format!(
+            "{}:{}:{}:{}:{}:{}",
+            ha1, challenge.nonce, nc, cnonce, qop, ha2
+        ))?,
+        None => h(format!("{}:{}:{}", ha1, challenge.nonce, ha2))?,
+    };
+
+    let mut header = format!(
+        "Digest username={}, realm={}, nonce={}, uri={}, response=\"{}\"",
+        quote(username),
+        quote(&challenge.realm),
+        quote(&challenge.nonce),
+        quote(uri),
+        response
+    );
+    if let Some(algorithm) = &challenge.algorithm {
+        header.push_str(&format!(", algorithm={}", algorithm));
+    }
+    if let Some(qop) = qop {
+        header.push_str(&format!(", qop={}, nc={}, cnonce=\"{}\"", qop, nc, cnonce));
+    }
+    if let Some(opaque) = &challenge.opaque {
+        header.push_str(&format!(", opaque={}", quote(opaque)));
+    }
+    Ok(header)
+}
+
+/// A fresh client nonce.
+pub fn cnonce() -> String {
+    let mut bytes = [0u8; 8];
+    let _ = openssl::rand::rand_bytes(&mut bytes);
+    hex(&bytes)
+}
+
+// ─── HMAC request signing ─────────────────────────────────────────────────────
+
+/// Headers signing one request at `timestamp` (Unix seconds).
+pub fn hmac_headers(
+    config: &HmacConfig,
+    method: &str,
+    url: &url::Url,
+    body: &[u8],
+    timestamp: i64,
+) -> Result<Vec<(String, String)>, String> {
+    let digest = match config.algorithm {
+        HmacAlgorithm::Sha256 => MessageDigest::sha256(),
+        HmacAlgorithm::Sha512 => MessageDigest::sha512(),
+    };
+    let string_to_sign = format!(
+        "{}\n{}\n{}\n{}",
+        method,
+        request_target(url),
+        timestamp,
+        digest_hex(MessageDigest::sha256(), body)?
+    );
+    let signature = hmac(
+        digest,
+        config.secret.expose().as_bytes(),
+        string_to_sign.as_bytes(),
+    )?;
+    let signature = match config.encoding {
+        SignatureEncoding::Hex => hex(&signature),
+        SignatureEncoding::Base64 => {
+            use base64::Engine as _;
+            base64::engine::general_purpose::STANDARD.encode(signature)
+        }
+    };
+
+    let mut headers = vec![(config.timestamp_header.clone(), timestamp.to_string())];
+    if let Some(key_id) = &config.key_id {
+        headers.push((config.key_id_header.clone(), key_id.clone()));
+    }
+    headers.push((config.signature_header.clone(), signature));
+    Ok(headers)
+}
+
+// ─── AWS Signature Version 4 ──────────────────────────────────────────────────
+
+/// Percent-encode everything but unreserved characters, as SigV4 requires.
+fn uri_encode(s: &str, keep_slash: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(b as char)
+            }
+            b'/' if keep_slash => out.push('/'),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+/// Headers signing one request at `now`; `Host` is signed as hyper sends it.
+pub fn sigv4_headers(
+    config: &AwsSigV4Config,
+    method: &str,
+    url: &url::Url,
+    body: &[u8],
+    now: DateTime<Utc>,
+) -> Result<Vec<(String, String)>, String> {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let payload_hash = digest_hex(MessageDigest::sha256(), body)?;
+    let is_s3 = config.service == "s3";
+
+    // S3 signs the path as sent; other services encode it once more.
+    let path = if url.path().is_empty() {
+        "/"
+    } else {
+        url.path()
+    };
+    let canonical_uri = if is_s3 {
+        path.to_string()
+    } else {
+        uri_encode(path, true)
+    };
+    let mut query: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(k, v)| (uri_encode(&k, false), uri_encode(&v, false)))
+        .collect();
+    query.sort();
+    let canonical_query = query
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let mut signed = vec![("host", host_header(url))];
+    if is_s3 {
+        signed.push(("x-amz-content-sha256", payload_hash.clone()));
+    }
+    signed.push(("x-amz-date", amz_date.clone()));
+    if let Some(token) = &config.session_token {
+        signed.push(("x-amz-security-token", token.expose().to_string()));
+    }
+    let canonical_headers: String = signed
+        .iter()
+        .map(|(k, v)| format!("{}:{}\n", k, v.trim()))
+        .collect();
+    let signed_headers = signed.iter().map(|(k, _)| *k).collect::<Vec<_>>().join(";");
+
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method, canonical_uri, canonical_query, canonical_headers, signed_headers, payload_hash
+    );
+    let scope = format!("{}/{}/{}/aws4_request", date, config.region, config.service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        digest_hex(MessageDigest::sha256(), canonical_request.as_bytes())?
+    );
+
+    let sha256 = MessageDigest::sha256();
+    let key = format!("AWS4{}", config.secret_access_key.expose());
+    let key = hmac(sha256, key.as_bytes(), date.as_bytes())?;
+    let key = hmac(sha256, &key, config.region.as_bytes())?;
+    let key = hmac(sha256, &key, config.service.as_bytes())?;
+    let key = hmac(sha256, &key, b"aws4_request")?;
+    let signature = hex(&hmac(sha256, &key, string_to_sign.as_bytes())?);
+
+    let mut headers = vec![("X-Amz-Date".to_string(), amz_date)];
+    if is_s3 {
+        headers.push(("X-Amz-Content-Sha256".to_string(), payload_hash));
+    }
+    if let Some(token) = &config.session_token {
+        headers.push((
+            "X-Amz-Security-Token".to_string(),
+            token.expose().to_string(),
+        ));
+    }
+    headers.push((
+        "Authorization".to_string(),
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            config.access_key_id, scope, signed_headers, signature
+        ),
+    ));
+    Ok(headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_digest_rfc2617_example() {
+        let challenge = parse_digest_challenge(
+            "Basic realm=\"x\", Digest realm=\"testrealm@host.com\", qop=\"auth,auth-int\", \
+             nonce=\"dcd98b7102dd2f0e8b11d0f600bfb0c093\", opaque=\"5ccc069c403ebaf9f0171e9517f40e41\"",
+        )
+        .unwrap();
+        assert_eq!(challenge.realm, "testrealm@host.com");
+        assert_eq!(challenge.qop.as_deref(), Some("auth,auth-int"));
+
+        let header = digest_authorization(
+            "Mufasa",
+            "Circle Of Life",
+            &challenge,
+            "GET",
+            "/dir/index.html",
+            b"",
+            1,
+            "0a4f113b",
+        )
+        .unwrap();
+        assert!(
+            header.contains("response=\"6629fae49393a05397450978507c4ef1\""),
+            "{}",
+            header
+        );
+        assert!(
+            header.contains("qop=auth, nc=00000001, cnonce=\"0a4f113b\""),
+            "{}",
+            header
+        );
+        assert!(
+            header.ends_with("opaque=\"5ccc069c403ebaf9f0171e9517f40e41\""),
+            "{}",
+            header
+        );
+    }
+
+    #[test]
+    fn test_digest_rejects_unknown_algorithm() {
+        let challenge =
+            parse_digest_challenge("Digest realm=\"r\", nonce=\"n\", algorithm=SHA-1").unwrap();
+        assert!(digest_authorization("u", "p", &challenge, "GET", "/", b"", 1, "c").is_err());
+        assert!(parse_digest_challenge("Basic realm=\"r\"").is_none());
+    }
+
+    #[test]
+    fn test_hmac_rfc4231_example() {
+        let mac = hmac(
+            MessageDigest::sha256(),
+            b"Jefe",
+            b"what do ya want for nothing?",
+        )
+        .unwrap();
+        assert_eq!(
+            hex(&mac),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_hmac_headers_sign_method_target_and_body() {
+        let config = HmacConfig {
+            secret: "secret".into(),
+            key_id: Some("monitor".to_string()),
+            algorithm: HmacAlgorithm::Sha256,
+            encoding: SignatureEncoding::Hex,
+            signature_header: "X-Signature".to_string(),
+            timestamp_header: "X-Timestamp".to_string(),
+            key_id_header: "X-Key-Id".to_string(),
+        };
+        let url = url::Url::parse("https://api.example.com/v1/status?full=1").unwrap();
+        let headers = hmac_headers(&config, "POST", &url, b"{}", 1700000000).unwrap();
+
+        let expected = hmac(
+            MessageDigest::sha256(),
+            b"secret",
+            format!(
+                "POST\n/v1/status?full=1\n1700000000\n{}",
+                digest_hex(MessageDigest::sha256(), b"{}").unwrap()
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(
+            headers,
+            vec![
+                ("X-Timestamp".to_string(), "1700000000".to_string()),
+                ("X-Key-Id".to_string(), "monitor".to_string()),
+                ("X-Signature".to_string(), hex(&expected)),
+            ]
+        );
+    }
+
+    fn aws_example() -> AwsSigV4Config {
+        AwsSigV4Config {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".into(),
+            session_token: None,
+            region: "us-east-1".to_string(),
+            service: "service".to_string(),
+        }
+    }
+
+    // Cases from the AWS Signature Version 4 test suite
+    #[test]
+    fn test_sigv4_test_suite() {
+        let now = Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap();
+        let authorization = |url: &str| {
+            let url = url::Url::parse(url).unwrap();
+            let headers = sigv4_headers(&aws_example(), "GET", &url, b"", now).unwrap();
+            assert_eq!(
+                headers[0],
+                ("X-Amz-Date".to_string(), "20150830T123600Z".to_string())
+            );
+            headers.last().unwrap().1.clone()
+        };
+
+        assert_eq!(
+            authorization("https://example.amazonaws.com/"),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+        assert!(
+            authorization("https://example.amazonaws.com/?Param2=value2&Param1=value1").ends_with(
+                "Signature=b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500"
+            )
+        );
+    }
+}
@@ -0,0 +1,405 @@
+use crate::config::{TlsClientConfig, TlsVersion};
+use log::{info, warn};
+use openssl::asn1::{Asn1Time, Asn1TimeRef};
+use openssl::nid::Nid;
+use openssl::ssl::{
+    ConnectConfiguration, Ssl, SslConnector, SslFiletype, SslMethod, SslRef, SslStream,
+    SslVerifyMode, SslVersion,
+};
+use openssl::x509::store::{X509Store, X509StoreBuilder};
+use openssl::x509::{X509NameRef, X509Ref, X509VerifyResult, X509};
+use std::io::{Read, Write};
+use std::net::{IpAddr, TcpStream};
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+// ─── Blocking TLS helpers shared by the line/frame protocol probes ───────────
+
+/// A connection that may have been upgraded to TLS (implicitly or via STARTTLS).
+pub enum MaybeTlsStream {
+    Plain(TcpStream),
+    Tls(Box<SslStream<TcpStream>>),
+}
+
+impl Read for MaybeTlsStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            MaybeTlsStream::Plain(s) => s.read(buf),
+            MaybeTlsStream::Tls(s) => s.read(buf),
+        }
+    }
+}
+
+impl Write for MaybeTlsStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            MaybeTlsStream::Plain(s) => s.write(buf),
+            MaybeTlsStream::Tls(s) => s.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            MaybeTlsStream::Plain(s) => s.flush(),
+            MaybeTlsStream::Tls(s) => s.flush(),
+        }
+    }
+}
+
+/// Certificate and session details captured during the TLS handshake.
+#[derive(Debug, Clone, Default)]
+pub struct TlsCertInfo {
+    pub days_remaining: Option<i64>,
+    /// Chain trusted, hostname matched and certificate not expired.
+    pub is_valid: bool,
+    pub chain_valid: bool,
+    pub hostname_matches: bool,
+    pub subject: Option<String>,
+    pub issuer: Option<String>,
+    pub serial: Option<String>,     // Upper-case hex
+    pub not_before: Option<String>, // RFC 3339, UTC
+    pub not_after: Option<String>,
+    pub signature_algorithm: Option<String>,
+    pub sans: Vec<String>,
+    pub protocol_version: Option<String>,
+    pub cipher: Option<String>,
+}
+
+/// Render an X.509 name as `CN=example.com, O=Example` (leaf-first RDN order).
+fn format_x509_name(name: &X509NameRef) -> String {
+    name.entries()
+        .map(|entry| {
+            let key = entry.object().nid().short_name().unwrap_or("?");
+            let value = entry
+                .data()
+                .as_utf8()
+                .map(|v| v.to_string())
+                .unwrap_or_default();
+            format!("{}={}", key, value)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn subject_alt_names(cert: &X509Ref) -> Vec<String> {
+    let Some(names) = cert.subject_alt_names() else {
+        return Vec::new();
+    };
+    names
+        .iter()
+        .filter_map(|name| {
+            if let Some(dns) = name.dnsname() {
+                return Some(dns.to_string());
+            }
+            match name.ipaddress()? {
+                [a, b, c, d] => Some(IpAddr::from([*a, *b, *c, *d]).to_string()),
+                bytes if bytes.len() == 16 => {
+                    let mut octets = [0u8; 16];
+                    octets.copy_from_slice(bytes);
+                    Some(IpAddr::from(octets).to_string())
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// RFC 6125 style matching: a single leading `*` label matches exactly one label.
+fn dns_name_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.trim_end_matches('.').to_ascii_lowercase();
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => host
+            .split_once('.')
+            .is_some_and(|(label, rest)| !label.is_empty() && rest == suffix),
+        None => pattern == host,
+    }
+}
+
+/// Convert an ASN.1 time to an RFC 3339 UTC timestamp.
+fn asn1_time_to_rfc3339(time: &Asn1TimeRef) -> Option<String> {
+    let epoch = Asn1Time::from_unix(0).ok()?;
+    let diff = epoch.diff(time).ok()?;
+    let secs = diff.days as i64 * 86_400 + diff.secs as i64;
+    chrono::DateTime::from_timestamp(secs, 0)
+        .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+}
+
+/// Extract the certificate fields of `TlsCertInfo` from a peer certificate.
+/// Session details and chain validity are left for the caller to fill in.
+pub fn certificate_info(host: &str, cert: &X509Ref) -> TlsCertInfo {
+    let sans = subject_alt_names(cert);
+    TlsCertInfo {
+        days_remaining: Asn1Time::days_from_now(0)
+            .ok()
+            .and_then(|now| now.diff(cert.not_after()).ok())
+            .map(|diff| diff.days as i64),
+        hostname_matches: hostname_matches(host, &sans, cert),
+        subject: Some(format_x509_name(cert.subject_name())),
+        issuer: Some(format_x509_name(cert.issuer_name())),
+        serial: cert
+            .serial_number()
+            .to_bn()
+            .and_then(|bn| bn.to_hex_str().map(|s| s.to_string()))
+            .ok(),
+        not_before: asn1_time_to_rfc3339(cert.not_before()),
+        not_after: asn1_time_to_rfc3339(cert.not_after()),
+        signature_algorithm: cert
+            .signature_algorithm()
+            .object()
+            .nid()
+            .long_name()
+            .ok()
+            .map(|s| s.to_string()),
+        sans,
+        ..Default::default()
+    }
+}
+
+/// Check `host` against the SANs, falling back to the subject CN only when the
+/// certificate carries no SANs at all.
+pub fn hostname_matches(host: &str, sans: &[String], cert: &X509Ref) -> bool {
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return sans
+            .iter()
+            .any(|san| san.parse::<IpAddr>().ok() == Some(ip));
+    }
+    if !sans.is_empty() {
+        return sans.iter().any(|san| dns_name_matches(san, host));
+    }
+    cert.subject_name()
+        .entries_by_nid(Nid::COMMONNAME)
+        .filter_map(|entry| entry.data().as_utf8().ok())
+        .any(|cn| dns_name_matches(&cn, host))
+}
+
+fn ssl_version(version: TlsVersion) -> SslVersion {
+    match version {
+        TlsVersion::Tls10 => SslVersion::TLS1,
+        TlsVersion::Tls11 => SslVersion::TLS1_1,
+        TlsVersion::Tls12 => SslVersion::TLS1_2,
+        TlsVersion::Tls13 => SslVersion::TLS1_3,
+    }
+}
+
+/// Trust exactly the certificates of a PEM bundle.
+fn load_ca_bundle(path: &str) -> Result<X509Store, String> {
+    let pem = std::fs::read(path).map_err(|e| format!("Cannot read CA bundle {}: {}", path, e))?;
+    let certs =
+        X509::stack_from_pem(&pem).map_err(|e| format!("Invalid CA bundle {}: {}", path, e))?;
+    if certs.is_empty() {
+        return Err(format!("CA bundle {} contains no certificates", path));
+    }
+    let mut store = X509StoreBuilder::new().map_err(|e| format!("TLS setup failed: {}", e))?;
+    for cert in certs {
+        store
+            .add_cert(cert)
+            .map_err(|e| format!("Invalid CA bundle {}: {}", path, e))?;
+    }
+    Ok(store.build())
+}
+
+/// Client handshake settings shared by the blocking and async connections.
+///
+/// The handshake completes even for untrusted certificates so the expiry can
+/// still be reported; chain and hostname verification are reflected in
+/// `TlsCertInfo` instead.
+pub fn client_configuration(options: &TlsClientConfig) -> Result<ConnectConfiguration, String> {
+    let mut builder = SslConnector::builder(SslMethod::tls_client())
+        .map_err(|e| format!("TLS setup failed: {}", e))?;
+    builder.set_verify(SslVerifyMode::NONE);
+    if let Some(version) = options.min_protocol_version {
+        builder
+            .set_min_proto_version(Some(ssl_version(version)))
+            .map_err(|e| format!("TLS setup failed: {}", e))?;
+    }
+    if let Some(ca_file) = &options.ca_file {
+        builder.set_cert_store(load_ca_bundle(ca_file)?);
+    }
+    match (&options.client_cert, &options.client_key) {
+        (Some(cert), Some(key)) => {
+            builder
+                .set_certificate_chain_file(cert)
+                .map_err(|e| format!("Cannot load client certificate {}: {}", cert, e))?;
+            builder
+                .set_private_key_file(key, SslFiletype::PEM)
+                .map_err(|e| format!("Cannot load client key {}: {}", key, e))?;
+            builder
+                .check_private_key()
+                .map_err(|e| format!("Client key {} does not match {}: {}", key, cert, e))?;
+        }
+        (None, None) => {}
+        _ => return Err("client_cert and client_key must be set together".to_string()),
+    }
+    let mut config = builder
+        .build()
+        .configure()
+        .map_err(|e| format!("TLS setup failed: {}", e))?;
+    config.set_verify_hostname(false);
+    Ok(config)
+}
+
+/// The name sent as SNI and checked against the certificate.
+pub fn server_name<'a>(host: &'a str, options: &'a TlsClientConfig) -> &'a str {
+    options.server_name.as_deref().unwrap_or(host)
+}
+
+/// An `Ssl` ready for a client handshake with `host` (SNI is set for names, not IPs).
+pub fn client_ssl(host: &str, options: &TlsClientConfig) -> Result<Ssl, String> {
+    client_configuration(options)?
+        .into_ssl(server_name(host, options))
+        .map_err(|e| format!("TLS setup failed: {}", e))
+}
+
+/// Perform a client handshake over an established TCP connection. With
+/// `verify` set, a peer whose certificate does not verify is refused.
+pub fn connect_tls(
+    host: &str,
+    tcp: TcpStream,
+    options: &TlsClientConfig,
+    verify: bool,
+) -> Result<(SslStream<TcpStream>, TlsCertInfo), String> {
+    let name = server_name(host, options);
+    let tls = client_configuration(options)?
+        .connect(name, tcp)
+        .map_err(|e| format!("TLS handshake failed: {}", e))?;
+    let info = session_info(name, tls.ssl());
+    if verify {
+        verify_peer(name, tls.ssl(), &info)?;
+    }
+    Ok((tls, info))
+}
+
+// ─── Async client handshake ───────────────────────────────────────────────────
+
+/// Duration and peer details of a completed client handshake.
+#[derive(Debug, Clone)]
+pub struct TlsHandshake {
+    pub duration: Duration,
+    pub certificate: TlsCertInfo,
+}
+
+/// Async counterpart of `connect_tls` that also times the handshake. The
+/// transport is usually a TCP connection, but may be a tunnel through a proxy.
+pub async fn connect_tls_async<S: AsyncRead + AsyncWrite + Unpin>(
+    host: &str,
+    transport: S,
+    options: &TlsClientConfig,
+    verify: bool,
+) -> Result<(tokio_openssl::SslStream<S>, TlsHandshake), String> {
+    let start = Instant::now();
+    let name = server_name(host, options);
+    let mut stream = tokio_openssl::SslStream::new(client_ssl(host, options)?, transport)
+        .map_err(|e| format!("TLS setup failed: {}", e))?;
+    Pin::new(&mut stream)
+        .connect()
+        .await
+        .map_err(|e| format!("TLS handshake failed: {}", e))?;
+    let duration = start.elapsed();
+
+    let certificate = session_info(name, stream.ssl());
+    if verify {
+        // Nothing is sent over a connection to an unverified peer
+        verify_peer(name, stream.ssl(), &certificate)?;
+    }
+    Ok((
+        stream,
+        TlsHandshake {
+            duration,
+            certificate,
+        },
+    ))
+}
+
+/// Fail unless the peer of a completed handshake presented a trusted, unexpired
+/// certificate chain for `host`.
+pub fn verify_peer(host: &str, ssl: &SslRef, info: &TlsCertInfo) -> Result<(), String> {
+    let verify_result = ssl.verify_result();
+    if verify_result != X509VerifyResult::OK {
+        return Err(format!(
+            "Certificate verification failed for {}: {}",
+            host,
+            verify_result.error_string()
+        ));
+    }
+    if !info.hostname_matches {
+        return Err(match info.sans.is_empty() {
+            true => format!("Certificate does not match {}", host),
+            false => format!(
+                "Certificate does not match {} (names: {})",
+                host,
+                info.sans.join(", ")
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Certificate and session details of a completed handshake.
+pub fn session_info(host: &str, ssl: &SslRef) -> TlsCertInfo {
+    let mut info = ssl
+        .peer_certificate()
+        .map(|cert| certificate_info(host, &cert))
+        .unwrap_or_default();
+    info.protocol_version = Some(ssl.version_str().to_string());
+    info.cipher = ssl.current_cipher().map(|c| c.name().to_string());
+
+    let verify_result = ssl.verify_result();
+    info.chain_valid = verify_result == X509VerifyResult::OK;
+    if !info.chain_valid {
+        warn!(
+            "Certificate verification failed for {}: {}",
+            host,
+            verify_result.error_string()
+        );
+    }
+    if !info.hostname_matches {
+        warn!("Certificate for {} does not match the hostname", host);
+    }
+    info.is_valid =
+        info.chain_valid && info.hostname_matches && info.days_remaining.is_some_and(|d| d > 0);
+    info!(
+        "TLS cert for {}: {:?} days remaining, valid: {}",
+        host, info.days_remaining, info.is_valid
+    );
+    info
+}
+
+// ─── Test certificates ────────────────────────────────────────────────────────
+
+/// Self-signed certificate for `localhost` and 127.0.0.1, valid for 30 days.
+#[cfg(test)]
+pub(crate) fn self_signed_cert() -> (X509, openssl::pkey::PKey<openssl::pkey::Private>) {
+    use openssl::hash::MessageDigest;
+    use openssl::pkey::PKey;
+    use openssl::rsa::Rsa;
+    use openssl::x509::extension::SubjectAlternativeName;
+    use openssl::x509::X509NameBuilder;
+
+    let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", "localhost").unwrap();
+    name.append_entry_by_text("O", "Ruptime Test").unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_issuer_name(&name).unwrap();
+    builder.set_pubkey(&key).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(30).unwrap())
+        .unwrap();
+    let san = SubjectAlternativeName::new()
+        .dns("localhost")
+        .ip("127.0.0.1")
+        .build(&builder.x509v3_context(None, None))
+        .unwrap();
+    builder.append_extension(san).unwrap();
+    builder.sign(&key, MessageDigest::sha256()).unwrap();
+    (builder.build(), key)
+}
@@ -15,6 +15,11 @@ pub enum CheckResult {
     MySQL(ServiceCheckResult),
     MongoDB(ServiceCheckResult),
     Elasticsearch(ServiceCheckResult),
+    Smtp(MailCheckResult),
+    Imap(MailCheckResult),
+    Pop3(MailCheckResult),
+    WebSocket(WebSocketCheckResult),
+    Tls(TlsCheckResult),
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -30,6 +35,11 @@ pub struct HttpCheckResultDetails {
     pub response_time_ms: u128,
     pub cert_days_remaining: Option<i64>,
     pub cert_is_valid: Option<bool>,
+    pub redirects: Vec<RedirectHop>,
+    pub timings: Option<HttpPhaseTimings>,
+    pub addresses: Vec<AddressCheckResult>,
+    pub oauth2_token_ok: Option<bool>, // OAuth2 checks only: was a token obtained?
+    pub assertions: Vec<AssertionResult>, // Every assertion evaluated, passed or not
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -39,12 +49,165 @@ pub struct ServiceCheckResult {
     pub service_info: Option<String>, // Database version, cluster info, etc.
 }
 
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MailCheckResult {
+    pub status: CheckStatus,
+    pub response_time_ms: u128,
+    pub service_info: Option<String>, // Server greeting banner
+    pub cert_days_remaining: Option<i64>,
+    pub cert_is_valid: Option<bool>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WebSocketCheckResult {
+    pub status: CheckStatus,
+    pub response_time_ms: u128,
+    pub handshake_time_ms: u128,
+    pub message_rtt_ms: Option<u128>, // Send-to-first-frame time when a message is sent
+    pub cert_days_remaining: Option<i64>,
+    pub cert_is_valid: Option<bool>,
+    pub assertions: Vec<AssertionResult>, // Every assertion evaluated, passed or not
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TlsCheckResult {
+    pub status: CheckStatus,
+    pub response_time_ms: u128,
+    pub cert_days_remaining: Option<i64>,
+    pub cert_is_valid: Option<bool>,
+    pub chain_valid: Option<bool>,
+    pub hostname_matches: Option<bool>,
+    pub subject: Option<String>,
+    pub issuer: Option<String>,
+    pub sans: Vec<String>,
+    pub protocol_version: Option<String>,
+    pub cipher: Option<String>,
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub enum CheckStatus {
     Healthy,
     Unhealthy(String), // Contains error message for debugging
 }
 
+impl CheckStatus {
+    fn scrub_secrets(&mut self) {
+        if let CheckStatus::Unhealthy(message) = self {
+            *message = crate::secrets::scrub(message);
+        }
+    }
+}
+
+impl AssertionResult {
+    fn scrub_secrets(&mut self) {
+        self.message = crate::secrets::scrub(&self.message);
+        if let Some(actual) = &mut self.actual {
+            *actual = crate::secrets::scrub(actual);
+        }
+    }
+}
+
+impl CheckResult {
+    /// The result with configured secrets removed from its failure messages
+    /// and assertion values, e.g. a password quoted by a database driver or a
+    /// token echoed in a response body. Applied before a result is logged or
+    /// stored.
+    pub fn redacted(mut self) -> Self {
+        match &mut self {
+            CheckResult::Tcp(r) => r.status.scrub_secrets(),
+            CheckResult::Http(r) => {
+                r.status.scrub_secrets();
+                for address in &mut r.addresses {
+                    address.status.scrub_secrets();
+                }
+                r.assertions
+                    .iter_mut()
+                    .for_each(AssertionResult::scrub_secrets);
+            }
+            CheckResult::Postgres(r)
+            | CheckResult::Redis(r)
+            | CheckResult::RabbitMQ(r)
+            | CheckResult::Kafka(r)
+            | CheckResult::MySQL(r)
+            | CheckResult::MongoDB(r)
+            | CheckResult::Elasticsearch(r) => r.status.scrub_secrets(),
+            CheckResult::Smtp(r) | CheckResult::Imap(r) | CheckResult::Pop3(r) => {
+                r.status.scrub_secrets()
+            }
+            CheckResult::WebSocket(r) => {
+                r.status.scrub_secrets();
+                r.assertions
+                    .iter_mut()
+                    .for_each(AssertionResult::scrub_secrets);
+            }
+            CheckResult::Tls(r) => r.status.scrub_secrets(),
+        }
+        self
+    }
+
+    pub fn status(&self) -> &CheckStatus {
+        match self {
+            CheckResult::Tcp(r) => &r.status,
+            CheckResult::Http(r) => &r.status,
+            CheckResult::Postgres(r)
+            | CheckResult::Redis(r)
+            | CheckResult::RabbitMQ(r)
+            | CheckResult::Kafka(r)
+            | CheckResult::MySQL(r)
+            | CheckResult::MongoDB(r)
+            | CheckResult::Elasticsearch(r) => &r.status,
+            CheckResult::Smtp(r) | CheckResult::Imap(r) | CheckResult::Pop3(r) => &r.status,
+            CheckResult::WebSocket(r) => &r.status,
+            CheckResult::Tls(r) => &r.status,
+        }
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        matches!(self.status(), CheckStatus::Healthy)
+    }
+
+    pub fn response_time_ms(&self) -> u128 {
+        match self {
+            CheckResult::Tcp(r) => r.response_time_ms,
+            CheckResult::Http(r) => r.response_time_ms,
+            CheckResult::Postgres(r)
+            | CheckResult::Redis(r)
+            | CheckResult::RabbitMQ(r)
+            | CheckResult::Kafka(r)
+            | CheckResult::MySQL(r)
+            | CheckResult::MongoDB(r)
+            | CheckResult::Elasticsearch(r) => r.response_time_ms,
+            CheckResult::Smtp(r) | CheckResult::Imap(r) | CheckResult::Pop3(r) => {
+                r.response_time_ms
+            }
+            CheckResult::WebSocket(r) => r.response_time_ms,
+            CheckResult::Tls(r) => r.response_time_ms,
+        }
+    }
+
+    /// The assertions evaluated by an HTTP or WebSocket check.
+    pub fn assertions(&self) -> &[AssertionResult] {
+        match self {
+            CheckResult::Http(r) => &r.assertions,
+            CheckResult::WebSocket(r) => &r.assertions,
+            _ => &[],
+        }
+    }
+
+    /// Certificate days remaining and validity, for checks that see one.
+    pub fn certificate(&self) -> Option<(Option<i64>, Option<bool>)> {
+        match self {
+            CheckResult::Http(r) => Some((r.cert_days_remaining, r.cert_is_valid)),
+            CheckResult::Smtp(r) | CheckResult::Imap(r) | CheckResult::Pop3(r) => {
+                Some((r.cert_days_remaining, r.cert_is_valid))
+            }
+            CheckResult::WebSocket(r) => Some((r.cert_days_remaining, r.cert_is_valid)),
+            CheckResult::Tls(r) => Some((r.cert_days_remaining, r.cert_is_valid)),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct TargetStatus {
     pub target_alias: String,
@@ -70,6 +233,8 @@ pub struct HistoricalCheckResult {
     pub is_healthy: bool,
     pub response_time_ms: Option<u128>,
     pub error_message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timings: Option<HttpPhaseTimings>, // HTTP checks only
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -89,6 +254,40 @@ pub struct HttpTargetCheckResult {
     pub response_time_ms: u128,
     pub cert_days_remaining: Option<i64>,
     pub cert_is_valid: Option<bool>,
+    pub redirects: Vec<RedirectHop>, // Followed redirects, in order
+    pub timings: Option<HttpPhaseTimings>, // None when no response was received
+    pub addresses: Vec<AddressCheckResult>, // Per-address results with check_each_address
+    pub oauth2_token_ok: Option<bool>, // OAuth2 checks only: was a token obtained?
+    pub assertions: Vec<AssertionResult>, // Every assertion evaluated, passed or not
+}
+
+/// The outcome of the check against one resolved address of the target.
+#[derive(Debug, Clone, Serialize)]
+pub struct AddressCheckResult {
+    pub address: String,
+    pub status: CheckStatus,
+    pub response_time_ms: u128,
+}
+
+/// Where the time of the final request went. DNS, connect and TLS are zero
+/// when a pooled keep-alive connection was reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct HttpPhaseTimings {
+    pub dns_ms: u128,
+    pub connect_ms: u128,
+    pub tls_ms: Option<u128>, // HTTPS only
+    pub ttfb_ms: u128,
+    pub download_ms: u128,
+    pub connection_reused: bool,
+}
+
+/// One followed redirect: the URL requested, its 3xx status and where it pointed.
+#[derive(Debug, Clone, Serialize)]
+pub struct RedirectHop {
+    pub url: String,
+    pub status_code: u16,
+    pub location: String,
+    pub duration_ms: u128,
 }
 
 // Helper for serializing SystemTime option
@@ -148,6 +347,7 @@ impl TargetStatus {
         is_healthy: bool,
         response_time_ms: Option<u128>,
         error_message: Option<String>,
+        timings: Option<HttpPhaseTimings>,
     ) {
         let now = SystemTime::now();
 
@@ -157,6 +357,7 @@ impl TargetStatus {
             is_healthy,
             response_time_ms,
             error_message,
+            timings,
         });
 
         // Keep only last 24 hours of data (assuming checks every 30 seconds = 2880 checks per day)
@@ -218,3 +419,43 @@ impl TargetStatus {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::Secret;
+
+    #[test]
+    fn test_redacted_scrubs_assertions() {
+        Secret::new("types-test-token");
+        let assertion = AssertionResult {
+            query: "body".to_string(),
+            predicate: "equals".to_string(),
+            passed: false,
+            message: "Expected \"ok\", got \"types-test-token\"".to_string(),
+            expected: "ok".to_string(),
+            actual: Some("types-test-token".to_string()),
+        };
+        let result = CheckResult::WebSocket(WebSocketCheckResult {
+            status: CheckStatus::Unhealthy("Bad reply types-test-token".to_string()),
+            response_time_ms: 0,
+            handshake_time_ms: 0,
+            message_rtt_ms: None,
+            cert_days_remaining: None,
+            cert_is_valid: None,
+            assertions: vec![assertion],
+        });
+
+        let CheckResult::WebSocket(redacted) = result.redacted() else {
+            unreachable!()
+        };
+        assert!(
+            matches!(&redacted.status, CheckStatus::Unhealthy(e) if e == "Bad reply [REDACTED]")
+        );
+        assert_eq!(
+            redacted.assertions[0].message,
+            "Expected \"ok\", got \"[REDACTED]\""
+        );
+        assert_eq!(redacted.assertions[0].actual.as_deref(), Some("[REDACTED]"));
+    }
+}
@@ -0,0 +1,453 @@
+use sxd_document::dom::{ChildOfElement, Element};
+use sxd_document::parser;
+use sxd_xpath::{Context, Factory, Value};
+
+// ─── XPath evaluation ─────────────────────────────────────────────────────────
+
+/// Evaluate an XPath 1.0 expression against a response body.
+///
+/// The body is parsed as XML unless the content type says HTML or XML parsing
+/// fails, in which case it is normalised with [`html_to_xml`] first. Namespace
+/// prefixes declared in the document (e.g. `soap:`) can be used in `path`.
+///
+/// Results map onto JSON values so they feed into `evaluate_predicate`:
+/// a single node yields its text, several nodes an array of texts, and
+/// `count()`, `boolean()` or `string()` expressions their typed value. An
+/// empty node-set, an unparsable body or an invalid expression yield `None`.
+pub fn evaluate_xpath(
+    body: &str,
+    path: &str,
+    content_type: Option<&str>,
+) -> Option<serde_json::Value> {
+    let xpath = match Factory::new().build(path) {
+        Ok(Some(xpath)) => xpath,
+        _ => return None,
+    };
+
+    let is_html = content_type.is_some_and(|ct| ct.to_ascii_lowercase().contains("html"));
+    let package = if is_html {
+        parser::parse(&html_to_xml(body)).ok()?
+    } else {
+        match parser::parse(body) {
+            Ok(package) => package,
+            Err(_) => parser::parse(&html_to_xml(body)).ok()?,
+        }
+    };
+    let document = package.as_document();
+
+    let mut context = Context::new();
+    for child in document.root().children() {
+        if let Some(element) = child.element() {
+            register_namespaces(&mut context, element);
+        }
+    }
+
+    let value = xpath.evaluate(&context, document.root()).ok()?;
+    match value {
+        Value::Nodeset(nodes) => {
+            let texts: Vec<serde_json::Value> = nodes
+                .document_order()
+                .iter()
+                .map(|node| serde_json::Value::String(node.string_value()))
+                .collect();
+            match texts.len() {
+                0 => None,
+                1 => texts.into_iter().next(),
+                _ => Some(serde_json::Value::Array(texts)),
+            }
+        }
+        Value::Boolean(b) => Some(serde_json::Value::Bool(b)),
+        Value::Number(n) => {
+            // Keep integral results (e.g. count()) comparable with `Integer` values.
+            if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+                Some(serde_json::Value::Number((n as i64).into()))
+            } else {
+                serde_json::Number::from_f64(n).map(serde_json::Value::Number)
+            }
+        }
+        Value::String(s) => Some(serde_json::Value::String(s)),
+    }
+}
+
+/// Make every prefixed namespace declared in the document usable from XPath.
+fn register_namespaces(context: &mut Context, element: Element) {
+    for namespace in element.namespaces_in_scope() {
+        if !namespace.prefix().is_empty() && namespace.prefix() != "xml" {
+            context.set_namespace(namespace.prefix(), namespace.uri());
+        }
+    }
+    for child in element.children() {
+        if let ChildOfElement::Element(child) = child {
+            register_namespaces(context, child);
+        }
+    }
+}
+
+// ─── Lenient HTML parsing ─────────────────────────────────────────────────────
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+/// Open elements that are implicitly closed when `tag` starts.
+fn implicitly_closed_by(tag: &str) -> &'static [&'static str] {
+    match tag {
+        "li" => &["li"],
+        "p" => &["p"],
+        "tr" => &["tr", "td", "th"],
+        "td" | "th" => &["td", "th"],
+        "dt" | "dd" => &["dt", "dd"],
+        "option" => &["option"],
+        _ => &[],
+    }
+}
+
+fn escape_xml(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '&' => out.push_str("&amp;"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// Copy text, keeping well-formed XML/numeric entities, translating a few common
+/// HTML named entities and escaping every other `&` and `<`.
+fn push_html_text(text: &str, out: &mut String) {
+    let mut rest = text;
+    while let Some(pos) = rest.find(['&', '<', '>']) {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        if !rest.starts_with('&') {
+            out.push_str(if rest.starts_with('<') {
+                "&lt;"
+            } else {
+                "&gt;"
+            });
+            rest = &rest[1..];
+            continue;
+        }
+        let entity = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .map(|end| &rest[1..=end]);
+        let replacement = match entity {
+            Some(name @ ("amp" | "lt" | "gt" | "quot" | "apos")) => Some(format!("&{};", name)),
+            Some("nbsp") => Some("&#160;".to_string()),
+            Some("copy") => Some("&#169;".to_string()),
+            Some("reg") => Some("&#174;".to_string()),
+            Some("ndash") => Some("&#8211;".to_string()),
+            Some("mdash") => Some("&#8212;".to_string()),
+            Some("hellip") => Some("&#8230;".to_string()),
+            Some(num) if num.starts_with('#') && num.len() > 1 => {
+                let valid = match num[1..].strip_prefix(['x', 'X']) {
+                    Some(hex) => !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()),
+                    None => num[1..].chars().all(|c| c.is_ascii_digit()),
+                };
+                valid.then(|| format!("&{};", num))
+            }
+            _ => None,
+        };
+        match (replacement, entity) {
+            (Some(replacement), Some(name)) => {
+                out.push_str(&replacement);
+                rest = &rest[name.len() + 2..];
+            }
+            _ => {
+                out.push_str("&amp;");
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+}
+
+/// Lower-case a tag or attribute name and make it a valid, namespace-free XML name.
+fn sanitize_name(name: &str) -> Option<String> {
+    let name: String = name
+        .chars()
+        .map(|c| match c.to_ascii_lowercase() {
+            c @ ('a'..='z' | '0'..='9' | '-' | '_' | '.') => c,
+            _ => '_',
+        })
+        .collect();
+    match name.chars().next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => Some(name),
+        _ => None,
+    }
+}
+
+struct Tag {
+    name: String,
+    attributes: Vec<(String, String)>,
+    closing: bool,
+    self_closing: bool,
+}
+
+/// Parse a tag starting at `<`; returns the tag and the number of bytes consumed.
+fn parse_tag(input: &str) -> Option<(Tag, usize)> {
+    let bytes = input.as_bytes();
+    let mut pos = 1;
+    let closing = bytes.get(pos) == Some(&b'/');
+    if closing {
+        pos += 1;
+    }
+    let name_start = pos;
+    while pos < bytes.len()
+        && !bytes[pos].is_ascii_whitespace()
+        && bytes[pos] != b'>'
+        && bytes[pos] != b'/'
+    {
+        pos += 1;
+    }
+    let name = sanitize_name(&input[name_start..pos])?;
+
+    let mut attributes: Vec<(String, String)> = Vec::new();
+    let mut self_closing = false;
+    loop {
+        while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        match bytes.get(pos) {
+            None => return None,
+            Some(b'>') => {
+                pos += 1;
+                break;
+            }
+            Some(b'/') => {
+                self_closing = true;
+                pos += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        let attr_start = pos;
+        while pos < bytes.len()
+            && !bytes[pos].is_ascii_whitespace()
+            && !matches!(bytes[pos], b'=' | b'>' | b'/')
+        {
+            pos += 1;
+        }
+        let attr_name = &input[attr_start..pos];
+        if attr_name.is_empty() {
+            // A stray `=` without a name
+            pos += 1;
+            continue;
+        }
+        while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+
+        let value = if bytes.get(pos) == Some(&b'=') {
+            pos += 1;
+            while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+                pos += 1;
+            }
+            match bytes.get(pos) {
+                Some(&quote @ (b'"' | b'\'')) => {
+                    let end = input[pos + 1..].find(quote as char)? + pos + 1;
+                    let value = input[pos + 1..end].to_string();
+                    pos = end + 1;
+                    value
+                }
+                _ => {
+                    let start = pos;
+                    while pos < bytes.len()
+                        && !bytes[pos].is_ascii_whitespace()
+                        && bytes[pos] != b'>'
+                    {
+                        pos += 1;
+                    }
+                    input[start..pos].to_string()
+                }
+            }
+        } else {
+            // Boolean attribute such as `disabled`
+            attr_name.to_ascii_lowercase()
+        };
+
+        let lower = attr_name.to_ascii_lowercase();
+        if lower == "xmlns" || lower.starts_with("xmlns:") {
+            continue;
+        }
+        if let Some(attr_name) = sanitize_name(attr_name) {
+            if !attributes.iter().any(|(n, _)| *n == attr_name) {
+                attributes.push((attr_name, value));
+            }
+        }
+    }
+
+    Some((
+        Tag {
+            name,
+            attributes,
+            closing,
+            self_closing,
+        },
+        pos,
+    ))
+}
+
+/// Convert tag-soup HTML into well-formed XML.
+///
+/// Tag and attribute names are lower-cased and stripped of namespaces, void
+/// elements are self-closed, unclosed elements are closed implicitly, stray
+/// end tags are dropped and `<script>`/`<style>` contents become escaped text.
+/// The result has a single `<html>` root so `/html/body/...` paths work even
+/// for fragments.
+pub fn html_to_xml(html: &str) -> String {
+    let mut out = String::with_capacity(html.len() + html.len() / 8);
+    let mut stack: Vec<String> = Vec::new();
+    let mut root_elements = 0;
+    let mut first_root = String::new();
+    let mut stray_text = false;
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            if stack.is_empty() && !rest.trim().is_empty() {
+                stray_text = true;
+            }
+            push_html_text(rest, &mut out);
+            break;
+        };
+        let text = &rest[..lt];
+        if stack.is_empty() && !text.trim().is_empty() {
+            stray_text = true;
+        }
+        push_html_text(text, &mut out);
+        rest = &rest[lt..];
+
+        // Comments, doctype and processing instructions are dropped.
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+            continue;
+        }
+
+        let Some((tag, consumed)) = parse_tag(rest) else {
+            out.push_str("&lt;");
+            rest = &rest[1..];
+            continue;
+        };
+        rest = &rest[consumed..];
+
+        if tag.closing {
+            if let Some(idx) = stack.iter().rposition(|open| *open == tag.name) {
+                while stack.len() > idx {
+                    let open = stack.pop().unwrap_or_default();
+                    out.push_str(&format!("</{}>", open));
+                }
+            }
+            continue;
+        }
+
+        let closes = implicitly_closed_by(&tag.name);
+        while stack
+            .last()
+            .is_some_and(|open| closes.contains(&open.as_str()))
+        {
+            let open = stack.pop().unwrap_or_default();
+            out.push_str(&format!("</{}>", open));
+        }
+
+        if stack.is_empty() {
+            root_elements += 1;
+            if root_elements == 1 {
+                first_root = tag.name.clone();
+            }
+        }
+
+        out.push('<');
+        out.push_str(&tag.name);
+        for (name, value) in &tag.attributes {
+            out.push(' ');
+            out.push_str(name);
+            out.push_str("=\"");
+            let mut decoded = String::new();
+            push_html_text(value, &mut decoded);
+            out.push_str(&decoded.replace('"', "&quot;"));
+            out.push('"');
+        }
+
+        if tag.self_closing || VOID_ELEMENTS.contains(&tag.name.as_str()) {
+            out.push_str("/>");
+            continue;
+        }
+        out.push('>');
+
+        if RAW_TEXT_ELEMENTS.contains(&tag.name.as_str()) {
+            let end_tag = format!("</{}", tag.name);
+            let end = rest
+                .to_ascii_lowercase()
+                .find(&end_tag)
+                .unwrap_or(rest.len());
+            escape_xml(&rest[..end], &mut out);
+            out.push_str(&format!("</{}>", tag.name));
+            rest = &rest[end..];
+            rest = rest.find('>').map_or("", |gt| &rest[gt + 1..]);
+            continue;
+        }
+
+        stack.push(tag.name);
+    }
+
+    while let Some(open) = stack.pop() {
+        out.push_str(&format!("</{}>", open));
+    }
+
+    if root_elements == 1 && first_root == "html" && !stray_text {
+        out
+    } else {
+        format!("<html>{}</html>", out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_to_xml_repairs_tag_soup() {
+        let html = r#"<!DOCTYPE html>
+<HTML><head><meta charset=utf-8><title>Status &amp; Health</title>
+<script>if (a < b && c) {}</script></head>
+<body class=main>
+<ul id="items"><li>One<li>Two &nbsp;<li>Three</ul>
+<p>First<p>Second<br>line & more
+<input type="checkbox" checked disabled>
+</div></body></html>"#;
+        let xml = html_to_xml(html);
+        assert!(parser::parse(&xml).is_ok(), "not well-formed: {}", xml);
+        assert_eq!(xml.matches("<html").count(), 1);
+        assert!(xml.contains("<li>One</li><li>Two &#160;</li><li>Three</li>"));
+        assert!(xml.contains(r#"<input type="checkbox" checked="checked" disabled="disabled"/>"#));
+        assert!(xml.contains("if (a &lt; b &amp;&amp; c) {}"));
+    }
+
+    #[test]
+    fn test_html_fragment_gets_single_root() {
+        let xml = html_to_xml("<div>a</div><div>b</div> trailing");
+        assert_eq!(xml, "<html><div>a</div><div>b</div> trailing</html>");
+        assert_eq!(
+            evaluate_xpath(&xml, "count(/html/div)", None),
+            Some(serde_json::json!(2))
+        );
+    }
+
+    #[test]
+    fn test_invalid_expression_yields_none() {
+        assert_eq!(evaluate_xpath("<a/>", "//[", None), None);
+        assert_eq!(evaluate_xpath("<a/>", "//missing", None), None);
+    }
+}
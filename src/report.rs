@@ -0,0 +1,537 @@
+use crate::config::{Check, HostConfig};
+use crate::monitoring::{run_check, AssertionResult, CheckResult, CheckStatus};
+use serde::Serialize;
+use std::time::Duration;
+
+/// The result of one check in a one-shot run, as reported to CI. Its
+/// assertions are those of `result`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Outcome {
+    pub name: String,
+    pub host: String,
+    #[serde(rename = "type")]
+    pub check_type: &'static str,
+    pub url: String,
+    pub healthy: bool,
+    pub response_time_ms: u128,
+    pub error: Option<String>,
+    pub result: CheckResult,
+}
+
+impl Outcome {
+    pub fn assertions(&self) -> &[AssertionResult] {
+        self.result.assertions()
+    }
+}
+
+/// Time allowed beyond the longest check timeout, for checks whose timeout
+/// covers each step (connect, handshake, query) rather than the whole check.
+const RUN_ONCE_GRACE: Duration = Duration::from_secs(10);
+
+/// Run every target once, all at the same time. Outcomes are in the order of
+/// `targets`. The run ends `RUN_ONCE_GRACE` after the longest check timeout,
+/// and checks still going then are reported as unhealthy.
+pub async fn run_once(targets: &[(&HostConfig, &Check)]) -> Vec<Outcome> {
+    let longest = targets
+        .iter()
+        .map(|(_, check)| check.timeout_seconds())
+        .max()
+        .unwrap_or(0);
+    run_within(targets, Duration::from_secs(longest) + RUN_ONCE_GRACE).await
+}
+
+async fn run_within(targets: &[(&HostConfig, &Check)], limit: Duration) -> Vec<Outcome> {
+    let deadline = tokio::time::Instant::now() + limit;
+    let mut runs = tokio::task::JoinSet::new();
+    for (index, (host, check)) in targets.iter().enumerate() {
+        let (address, check) = (host.address.clone(), (*check).clone());
+        runs.spawn(async move { (index, run_check(&address, &check).await.redacted()) });
+    }
+    let mut results: Vec<Option<CheckResult>> = vec![None; targets.len()];
+    let mut timed_out = false;
+    loop {
+        match tokio::time::timeout_at(deadline, runs.join_next()).await {
+            Ok(Some(Ok((index, result)))) => results[index] = Some(result),
+            Ok(Some(Err(_))) => {}
+            Ok(None) => break,
+            Err(_) => {
+                timed_out = true; // Dropping the set aborts the checks still running
+                break;
+            }
+        }
+    }
+
+    targets
+        .iter()
+        .zip(results)
+        .map(|((host, check), result)| {
+            // A check that panicked or ran out of time is reported like a TCP failure
+            let result = result.unwrap_or_else(|| {
+                let error = match timed_out {
+                    true => format!("Check did not finish within {}s", limit.as_secs()),
+                    false => "Check task failed".to_string(),
+                };
+                CheckResult::Tcp(crate::monitoring::TcpCheckResult {
+                    status: CheckStatus::Unhealthy(error),
+                    response_time_ms: 0,
+                })
+            });
+            Outcome {
+                name: host.target_alias(check),
+                host: host.address.clone(),
+                check_type: check.kind(),
+                url: host.monitor_url(check),
+                healthy: result.is_healthy(),
+                response_time_ms: result.response_time_ms(),
+                error: match result.status() {
+                    CheckStatus::Unhealthy(e) => Some(e.clone()),
+                    CheckStatus::Healthy => None,
+                },
+                result,
+            }
+        })
+        .collect()
+}
+
+// ─── Formats ─────────────────────────────────────────────────────────────────
+
+/// An aligned table with a line per check, its assertions below it, and a
+/// summary.
+pub fn table(outcomes: &[Outcome]) -> String {
+    let name_width = outcomes
+        .iter()
+        .map(|o| o.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    let type_width = outcomes
+        .iter()
+        .map(|o| o.check_type.len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    let mut out = format!(
+        "{:<9}  {:<name_width$}  {:<type_width$}  {:>8}  DETAILS\n",
+        "STATUS", "NAME", "TYPE", "TIME"
+    );
+    for outcome in outcomes {
+        let status = if outcome.healthy {
+            "HEALTHY"
+        } else {
+            "UNHEALTHY"
+        };
+        let row = format!(
+            "{:<9}  {:<name_width$}  {:<type_width$}  {:>6}ms  {}",
+            status,
+            outcome.name,
+            outcome.check_type,
+            outcome.response_time_ms,
+            outcome.error.as_deref().unwrap_or(""),
+        );
+        out.push_str(row.trim_end());
+        out.push('\n');
+        for assertion in outcome.assertions() {
+            match assertion.passed {
+                true => out.push_str(&format!(
+                    "    pass  {} {} {}\n",
+                    assertion.query, assertion.predicate, assertion.expected
+                )),
+                false => out.push_str(&format!("    FAIL  {}\n", assertion.message)),
+            }
+        }
+    }
+    let unhealthy = outcomes.iter().filter(|o| !o.healthy).count();
+    out.push_str(&format!(
+        "\n{} checks: {} healthy, {} unhealthy\n",
+        outcomes.len(),
+        outcomes.len() - unhealthy,
+        unhealthy
+    ));
+    out
+}
+
+pub fn json(outcomes: &[Outcome]) -> String {
+    let unhealthy = outcomes.iter().filter(|o| !o.healthy).count();
+    let report = serde_json::json!({
+        "healthy": unhealthy == 0,
+        "total": outcomes.len(),
+        "unhealthy": unhealthy,
+        "results": outcomes,
+    });
+    serde_json::to_string_pretty(&report).unwrap_or_default()
+}
+
+/// A JUnit XML report: each check is a test suite holding a test case for
+/// the check itself, failing with its `Unhealthy` message, and one per
+/// assertion, failing with its expected and actual values.
+pub fn junit(outcomes: &[Outcome]) -> String {
+    let failed_assertions = |o: &Outcome| o.assertions().iter().filter(|a| !a.passed).count();
+    let tests: usize = outcomes.iter().map(|o| 1 + o.assertions().len()).sum();
+    let failures: usize = outcomes
+        .iter()
+        .map(|o| usize::from(!o.healthy) + failed_assertions(o))
+        .sum();
+    let total_seconds: f64 = outcomes.iter().map(|o| seconds(o.response_time_ms)).sum();
+    let timestamp = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S");
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        "<testsuites name=\"uptime_monitor\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n",
+        tests, failures, total_seconds
+    ));
+    for outcome in outcomes {
+        let classname = format!("{}.{}", outcome.check_type, escape_attribute(&outcome.host));
+        out.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"0\" time=\"{:.3}\" timestamp=\"{}\">\n",
+            escape_attribute(&outcome.name),
+            1 + outcome.assertions().len(),
+            usize::from(!outcome.healthy) + failed_assertions(outcome),
+            seconds(outcome.response_time_ms),
+            timestamp
+        ));
+        out.push_str("    <properties>\n");
+        out.push_str(&format!(
+            "      <property name=\"url\" value=\"{}\"/>\n",
+            escape_attribute(&outcome.url)
+        ));
+        out.push_str("    </properties>\n");
+        let testcase = format!(
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+            escape_attribute(&outcome.name),
+            classname,
+            seconds(outcome.response_time_ms)
+        );
+        match &outcome.error {
+            Some(error) => out.push_str(&format!(
+                "{}>\n      <failure message=\"{}\" type=\"Unhealthy\"/>\n    </testcase>\n",
+                testcase,
+                escape_attribute(error)
+            )),
+            None => out.push_str(&format!("{}/>\n", testcase)),
+        }
+        for assertion in outcome.assertions() {
+            let testcase = format!(
+                "    <testcase name=\"{} {} {}\" classname=\"{}\" time=\"0.000\"",
+                escape_attribute(&assertion.query),
+                escape_attribute(&assertion.predicate),
+                escape_attribute(&assertion.expected),
+                classname
+            );
+            if assertion.passed {
+                out.push_str(&format!("{}/>\n", testcase));
+                continue;
+            }
+            out.push_str(&format!(
+                "{}>\n      <failure message=\"{}\" type=\"AssertionFailed\">expected: {}\nactual: {}</failure>\n    </testcase>\n",
+                testcase,
+                escape_attribute(&assertion.message),
+                escape_xml(&assertion.expected),
+                escape_xml(assertion.actual.as_deref().unwrap_or("no value"))
+            ));
+        }
+        out.push_str("  </testsuite>\n");
+    }
+    out.push_str("</testsuites>\n");
+    out
+}
+
+/// A TAP 14 report: each check is a test point, and its assertions a subtest
+/// with expected and actual values in YAML diagnostics.
+pub fn tap(outcomes: &[Outcome]) -> String {
+    let mut out = format!("TAP version 14\n1..{}\n", outcomes.len());
+    for (i, outcome) in outcomes.iter().enumerate() {
+        if !outcome.assertions().is_empty() {
+            out.push_str(&format!("# Subtest: {}\n", tap_description(&outcome.name)));
+            out.push_str(&format!("    1..{}\n", outcome.assertions().len()));
+            for (j, assertion) in outcome.assertions().iter().enumerate() {
+                let description = format!(
+                    "{} {} {}",
+                    assertion.query, assertion.predicate, assertion.expected
+                );
+                out.push_str(&format!(
+                    "    {} {} - {}\n",
+                    if assertion.passed { "ok" } else { "not ok" },
+                    j + 1,
+                    tap_description(&description)
+                ));
+                if !assertion.passed {
+                    let actual = assertion.actual.as_deref().unwrap_or("(no value)");
+                    out.push_str("      ---\n");
+                    out.push_str(&format!(
+                        "      expected: {}\n",
+                        yaml_string(&assertion.expected)
+                    ));
+                    out.push_str(&format!("      actual: {}\n", yaml_string(actual)));
+                    out.push_str("      ...\n");
+                }
+            }
+        }
+        out.push_str(&format!(
+            "{} {} - {}\n",
+            if outcome.healthy { "ok" } else { "not ok" },
+            i + 1,
+            tap_description(&outcome.name)
+        ));
+        if let Some(error) = &outcome.error {
+            out.push_str("  ---\n");
+            out.push_str(&format!("  message: {}\n", yaml_string(error)));
+            out.push_str(&format!("  type: {}\n", outcome.check_type));
+            out.push_str(&format!("  url: {}\n", yaml_string(&outcome.url)));
+            out.push_str(&format!("  duration_ms: {}\n", outcome.response_time_ms));
+            out.push_str("  ...\n");
+        }
+    }
+    out
+}
+
+fn seconds(ms: u128) -> f64 {
+    ms as f64 / 1000.0
+}
+
+// Control characters other than tab and newlines are not allowed in XML 1.0
+fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '&' => out.push_str("&amp;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// Attribute values would have their line breaks normalised to spaces
+fn escape_attribute(text: &str) -> String {
+    escape_xml(text)
+        .replace('\n', "&#10;")
+        .replace('\r', "&#13;")
+}
+
+// `#` starts a directive such as `# SKIP`, and a test point is one line
+fn tap_description(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('#', "\\#")
+        .replace(['\n', '\r'], " ")
+}
+
+// A JSON string is a valid double-quoted YAML scalar
+fn yaml_string(text: &str) -> String {
+    serde_json::to_string(text).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::monitoring::HttpCheckResultDetails;
+
+    fn outcome(name: &str, status: CheckStatus, assertions: Vec<AssertionResult>) -> Outcome {
+        let result = CheckResult::Http(HttpCheckResultDetails {
+            status: status.clone(),
+            response_time_ms: 42,
+            cert_days_remaining: None,
+            cert_is_valid: None,
+            redirects: Vec::new(),
+            timings: None,
+            addresses: Vec::new(),
+            oauth2_token_ok: None,
+            assertions,
+        });
+        Outcome {
+            name: name.to_string(),
+            host: "example.com".to_string(),
+            check_type: "HTTP",
+            url: "https://example.com:443/".to_string(),
+            healthy: result.is_healthy(),
+            response_time_ms: 42,
+            error: match status {
+                CheckStatus::Unhealthy(e) => Some(e),
+                CheckStatus::Healthy => None,
+            },
+            result,
+        }
+    }
+
+    fn assertion(passed: bool) -> AssertionResult {
+        AssertionResult {
+            query: "jsonpath[$.status]".to_string(),
+            predicate: "equals".to_string(),
+            passed,
+            message: match passed {
+                true => "jsonpath[$.status] equals \"ok\" ✓".to_string(),
+                false => "jsonpath[$.status] equals \"ok\" (got: \"down\")".to_string(),
+            },
+            expected: "\"ok\"".to_string(),
+            actual: Some(if passed { "\"ok\"" } else { "\"down\"" }.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_table_lists_checks_assertions_and_summary() {
+        let outcomes = [
+            outcome("API", CheckStatus::Healthy, vec![assertion(true)]),
+            outcome(
+                "Status page",
+                CheckStatus::Unhealthy("Assertion failures: ...".to_string()),
+                vec![assertion(false)],
+            ),
+        ];
+        let table = table(&outcomes);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "STATUS     NAME         TYPE      TIME  DETAILS",
+                "HEALTHY    API          HTTP      42ms",
+                "    pass  jsonpath[$.status] equals \"ok\"",
+                "UNHEALTHY  Status page  HTTP      42ms  Assertion failures: ...",
+                "    FAIL  jsonpath[$.status] equals \"ok\" (got: \"down\")",
+                "",
+                "2 checks: 1 healthy, 1 unhealthy",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_summarises_overall_health() {
+        let outcomes = [outcome(
+            "API",
+            CheckStatus::Unhealthy("HTTP 503".to_string()),
+            vec![assertion(false)],
+        )];
+        let report: serde_json::Value = serde_json::from_str(&json(&outcomes)).unwrap();
+        assert_eq!(report["healthy"], false);
+        assert_eq!(report["total"], 1);
+        assert_eq!(report["unhealthy"], 1);
+        let result = &report["results"][0];
+        assert_eq!(result["name"], "API");
+        assert_eq!(result["type"], "HTTP");
+        assert_eq!(result["error"], "HTTP 503");
+        assert!(result.get("assertions").is_none());
+        let assertions = &result["result"]["Http"]["assertions"];
+        assert_eq!(assertions[0]["passed"], false);
+        assert_eq!(assertions[0]["actual"], "\"down\"");
+    }
+
+    #[tokio::test]
+    async fn test_run_once_reports_checks_past_the_deadline() {
+        // Accepted by the backlog but never answered
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let config: AppConfig = toml::from_str(&format!(
+            r#"
+[[hosts]]
+address = "127.0.0.1"
+  [[hosts.checks]]
+  type = "Http"
+  port = {}
+  path = "/"
+  protocol = "Http"
+  method = "Get"
+  timeout_seconds = 30
+"#,
+            listener.local_addr().unwrap().port()
+        ))
+        .unwrap();
+        let targets = [(&config.hosts[0], &config.hosts[0].checks[0])];
+
+        let start = std::time::Instant::now();
+        let outcomes = run_within(&targets, Duration::from_secs(1)).await;
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(!outcomes[0].healthy);
+        assert_eq!(
+            outcomes[0].error.as_deref(),
+            Some("Check did not finish within 1s")
+        );
+    }
+
+    fn mixed_outcomes() -> [Outcome; 2] {
+        [
+            outcome("API", CheckStatus::Healthy, vec![assertion(true)]),
+            outcome(
+                "Status <page> #2",
+                CheckStatus::Unhealthy("Assertion failures: \"down\"\nretry".to_string()),
+                vec![assertion(true), assertion(false)],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_junit_reports_checks_as_suites_of_assertions() {
+        let xml = junit(&mixed_outcomes());
+        let lines: Vec<&str> = xml.lines().collect();
+        assert_eq!(lines[0], "<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+        assert_eq!(
+            lines[1],
+            "<testsuites name=\"uptime_monitor\" tests=\"5\" failures=\"2\" time=\"0.084\">"
+        );
+        assert!(lines[2].starts_with(
+            "  <testsuite name=\"API\" tests=\"2\" failures=\"0\" errors=\"0\" skipped=\"0\" time=\"0.042\""
+        ));
+        assert!(lines[9].starts_with(
+            "  <testsuite name=\"Status &lt;page&gt; #2\" tests=\"3\" failures=\"2\" errors=\"0\""
+        ));
+        let passed = "    <testcase name=\"jsonpath[$.status] equals &quot;ok&quot;\" classname=\"HTTP.example.com\" time=\"0.000\"/>";
+        assert_eq!(
+            &lines[3..9],
+            [
+                "    <properties>",
+                "      <property name=\"url\" value=\"https://example.com:443/\"/>",
+                "    </properties>",
+                "    <testcase name=\"API\" classname=\"HTTP.example.com\" time=\"0.042\"/>",
+                passed,
+                "  </testsuite>",
+            ]
+        );
+        assert_eq!(
+            &lines[10..],
+            [
+                "    <properties>",
+                "      <property name=\"url\" value=\"https://example.com:443/\"/>",
+                "    </properties>",
+                "    <testcase name=\"Status &lt;page&gt; #2\" classname=\"HTTP.example.com\" time=\"0.042\">",
+                "      <failure message=\"Assertion failures: &quot;down&quot;&#10;retry\" type=\"Unhealthy\"/>",
+                "    </testcase>",
+                passed,
+                "    <testcase name=\"jsonpath[$.status] equals &quot;ok&quot;\" classname=\"HTTP.example.com\" time=\"0.000\">",
+                "      <failure message=\"jsonpath[$.status] equals &quot;ok&quot; (got: &quot;down&quot;)\" type=\"AssertionFailed\">expected: &quot;ok&quot;",
+                "actual: &quot;down&quot;</failure>",
+                "    </testcase>",
+                "  </testsuite>",
+                "</testsuites>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tap_reports_assertions_as_subtests() {
+        assert_eq!(
+            tap(&mixed_outcomes()),
+            r#"TAP version 14
+1..2
+# Subtest: API
+    1..1
+    ok 1 - jsonpath[$.status] equals "ok"
+ok 1 - API
+# Subtest: Status <page> \#2
+    1..2
+    ok 1 - jsonpath[$.status] equals "ok"
+    not ok 2 - jsonpath[$.status] equals "ok"
+      ---
+      expected: "\"ok\""
+      actual: "\"down\""
+      ...
+not ok 2 - Status <page> \#2
+  ---
+  message: "Assertion failures: \"down\"\nretry"
+  type: HTTP
+  url: "https://example.com:443/"
+  duration_ms: 42
+  ...
+"#
+        );
+    }
+}
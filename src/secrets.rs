@@ -0,0 +1,302 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::{LazyLock, RwLock};
+
+/// Shown instead of a secret.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Shorter values would scrub ordinary words from messages.
+const MIN_SCRUBBED_LEN: usize = 4;
+
+// Every secret loaded so far, as written and percent-encoded (as in URIs)
+static KNOWN_SECRETS: LazyLock<RwLock<BTreeSet<String>>> =
+    LazyLock::new(|| RwLock::new(BTreeSet::new()));
+
+/// A sensitive configuration value such as a password or token. It prints as
+/// `[REDACTED]` in `Debug` output and when serialized, and [`scrub`] removes
+/// it from messages.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        let value = value.into();
+        if value.len() >= MIN_SCRUBBED_LEN {
+            let mut known = KNOWN_SECRETS.write().unwrap();
+            known.insert(urlencoding::encode(&value).into_owned());
+            known.insert(value.clone());
+        }
+        Secret(value)
+    }
+
+    /// The value itself, for use in requests and connection strings.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Secret::new(value)
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Secret::new(value)
+    }
+}
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Secret::new)
+    }
+}
+
+// In the schema a secret is the string it is written as
+impl schemars::JsonSchema for Secret {
+    fn schema_name() -> String {
+        String::schema_name()
+    }
+
+    fn json_schema(generator: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(generator)
+    }
+
+    fn is_referenceable() -> bool {
+        false
+    }
+}
+
+/// Replace every known secret in `text` with `[REDACTED]`, e.g. in driver
+/// errors that quote a connection string.
+pub fn scrub(text: &str) -> String {
+    let known = KNOWN_SECRETS.read().unwrap();
+    let mut secrets: Vec<&String> = known.iter().filter(|s| text.contains(s.as_str())).collect();
+    // Longest first, so a secret containing another is replaced whole
+    secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    secrets.into_iter().fold(text.to_string(), |text, secret| {
+        text.replace(secret.as_str(), REDACTED)
+    })
+}
+
+/// Resolve secret references in every string of a parsed configuration:
+/// `${VAR}` (or `${VAR:-default}`) anywhere in a string is replaced by the
+/// environment variable, `$${` stands for a literal `${`, and a whole value of
+/// `env:VAR` or `file:PATH` is replaced by the variable or the file contents
+/// (`$env:` and `$file:` are literal `env:` and `file:`).
+/// Relative paths are taken from `base_dir`, the directory of the config file.
+pub fn resolve(value: &mut toml::Value, base_dir: &Path) -> Result<(), String> {
+    resolve_at(value, base_dir, &mut String::new())
+}
+
+fn resolve_at(
+    value: &mut toml::Value,
+    base_dir: &Path,
+    key_path: &mut String,
+) -> Result<(), String> {
+    let len = key_path.len();
+    match value {
+        toml::Value::String(s) => *s = resolve_string(s, base_dir, key_path)?,
+        toml::Value::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                key_path.push_str(&format!("[{}]", i));
+                resolve_at(item, base_dir, key_path)?;
+                key_path.truncate(len);
+            }
+        }
+        toml::Value::Table(table) => {
+            for (key, item) in table.iter_mut() {
+                if !key_path.is_empty() {
+                    key_path.push('.');
+                }
+                key_path.push_str(key);
+                resolve_at(item, base_dir, key_path)?;
+                key_path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn resolve_string(s: &str, base_dir: &Path, key_path: &str) -> Result<String, String> {
+    let s = interpolate(s, key_path)?;
+    if s.starts_with("$env:") || s.starts_with("$file:") {
+        return Ok(s[1..].to_string());
+    }
+    if let Some(name) = s.strip_prefix("env:") {
+        return std::env::var(name).map_err(|_| {
+            format!(
+                "Environment variable '{}' referenced by '{}' is not set",
+                name, key_path
+            )
+        });
+    }
+    if let Some(file) = s.strip_prefix("file:") {
+        let path = base_dir.join(file);
+        let contents = std::fs::read_to_string(&path).map_err(|e| {
+            format!(
+                "Cannot read secret file '{}' referenced by '{}': {}",
+                path.display(),
+                key_path,
+                e
+            )
+        })?;
+        // Files written by editors and `echo` end with a newline that is not part of the secret
+        let trimmed = contents.strip_suffix('\n').unwrap_or(&contents);
+        return Ok(trimmed.strip_suffix('\r').unwrap_or(trimmed).to_string());
+    }
+    Ok(s)
+}
+
+/// Replace `${VAR}` and `${VAR:-default}` with environment variables.
+fn interpolate(s: &str, key_path: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(pos) = rest.find("${") {
+        if rest[..pos].ends_with('$') {
+            out.push_str(&rest[..pos - 1]); // `$${` is a literal `${`
+            out.push_str("${");
+            rest = &rest[pos + 2..];
+            continue;
+        }
+        out.push_str(&rest[..pos]);
+        let end = rest[pos..]
+            .find('}')
+            .ok_or_else(|| format!("Unterminated '${{' in '{}'", key_path))?;
+        let reference = &rest[pos + 2..pos + end];
+        let (name, default) = match reference.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (reference, None),
+        };
+        if name.is_empty() {
+            return Err(format!("Empty variable name in '${{}}' in '{}'", key_path));
+        }
+        match (std::env::var(name), default) {
+            (Ok(value), Some(default)) if value.is_empty() => out.push_str(default),
+            (Ok(value), _) => out.push_str(&value),
+            (Err(_), Some(default)) => out.push_str(default),
+            (Err(_), None) => {
+                return Err(format!(
+                    "Environment variable '{}' referenced by '{}' is not set",
+                    name, key_path
+                ))
+            }
+        }
+        rest = &rest[pos + end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn resolved(toml_content: &str, base_dir: &Path) -> Result<toml::Value, String> {
+        let mut value: toml::Value = toml::from_str(toml_content).unwrap();
+        resolve(&mut value, base_dir).map(|_| value)
+    }
+
+    #[test]
+    fn test_secret_is_redacted_and_scrubbed() {
+        let secret = Secret::new("p@ss/word");
+        assert_eq!(format!("{:?}", secret), "[REDACTED]");
+        assert_eq!(serde_json::to_string(&secret).unwrap(), "\"[REDACTED]\"");
+        assert_eq!(secret.expose(), "p@ss/word");
+
+        assert_eq!(
+            scrub("auth failed for mongodb://monitor:p%40ss%2Fword@db and password=p@ss/word"),
+            "auth failed for mongodb://monitor:[REDACTED]@db and password=[REDACTED]"
+        );
+        Secret::new("abc"); // Too short to scrub
+        assert_eq!(scrub("abc"), "abc");
+    }
+
+    #[test]
+    fn test_interpolates_environment_variables() {
+        std::env::set_var("SECRETS_TEST_USER", "monitor");
+        std::env::set_var("SECRETS_TEST_EMPTY", "");
+        std::env::remove_var("SECRETS_TEST_UNSET");
+
+        let value = resolved(
+            r#"
+url = "postgres://${SECRETS_TEST_USER}@db/${SECRETS_TEST_UNSET:-app}"
+empty = "${SECRETS_TEST_EMPTY:-fallback}"
+literal = "cost: $${SECRETS_TEST_USER} and $5"
+[[hosts]]
+users = ["${SECRETS_TEST_USER}"]
+"#,
+            Path::new("."),
+        )
+        .unwrap();
+        assert_eq!(value["url"].as_str(), Some("postgres://monitor@db/app"));
+        assert_eq!(value["empty"].as_str(), Some("fallback"));
+        assert_eq!(
+            value["literal"].as_str(),
+            Some("cost: ${SECRETS_TEST_USER} and $5")
+        );
+        assert_eq!(value["hosts"][0]["users"][0].as_str(), Some("monitor"));
+
+        let err = resolved(
+            "[[hosts]]\npassword = \"${SECRETS_TEST_UNSET}\"",
+            Path::new("."),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            "Environment variable 'SECRETS_TEST_UNSET' referenced by 'hosts[0].password' is not set"
+        );
+        assert!(resolved("a = \"${OPEN\"", Path::new(".")).is_err());
+    }
+
+    #[test]
+    fn test_resolves_env_and_file_references() {
+        std::env::set_var("SECRETS_TEST_TOKEN", "t0ken");
+        let dir = tempfile::tempdir().unwrap();
+        let mut file = std::fs::File::create(dir.path().join("db_password")).unwrap();
+        writeln!(file, "s3cret").unwrap();
+
+        let value = resolved(
+            r#"
+token = "env:SECRETS_TEST_TOKEN"
+password = "file:db_password"
+"#,
+            dir.path(),
+        )
+        .unwrap();
+        assert_eq!(value["token"].as_str(), Some("t0ken"));
+        assert_eq!(value["password"].as_str(), Some("s3cret"));
+
+        let err = resolved("password = \"file:missing\"", dir.path()).unwrap_err();
+        assert!(err.starts_with("Cannot read secret file"), "{}", err);
+        assert!(err.contains("referenced by 'password'"), "{}", err);
+        assert!(resolved("token = \"env:SECRETS_TEST_UNSET_TOO\"", dir.path()).is_err());
+
+        let value = resolved(
+            r#"
+expected = "$env:production"
+header = "$file:upload"
+"#,
+            dir.path(),
+        )
+        .unwrap();
+        assert_eq!(value["expected"].as_str(), Some("env:production"));
+        assert_eq!(value["header"].as_str(), Some("file:upload"));
+    }
+}
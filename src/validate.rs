@@ -0,0 +1,537 @@
+use crate::config::{
+    load_config_files, AppConfig, AssertionPredicate, AssertionQuery, AssertionValue, AuthConfig,
+    Check, HttpAssertion, HttpCheck, TlsClientConfig,
+};
+use crate::includes::ConfigFile;
+use crate::monitoring::proxy::{self, Proxy};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A problem found in a configuration and the setting it concerns, e.g.
+/// `hosts[0].checks[1].body_regex_check`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub location: String,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+struct Issues(Vec<Issue>);
+
+impl Issues {
+    fn push(&mut self, location: impl Into<String>, message: impl Into<String>) {
+        self.0.push(Issue {
+            location: location.into(),
+            message: message.into(),
+        });
+    }
+}
+
+/// Load `file_path` and validate it. Returns the configuration, or every
+/// problem found, a configuration that does not load being a single one.
+pub fn load_and_validate(file_path: &str) -> Result<AppConfig, Vec<Issue>> {
+    let (config, files) = load_config_files(file_path).map_err(|message| {
+        vec![Issue {
+            location: file_path.to_string(),
+            message,
+        }]
+    })?;
+
+    let mut issues = unknown_settings(&files, &config);
+    issues.extend(validate(&config, &files));
+    match issues.is_empty() {
+        true => Ok(config),
+        false => Err(issues),
+    }
+}
+
+// Where a setting is written. Across several files, locations start with the
+// file and count hosts from the start of it.
+struct Sources<'a>(&'a [ConfigFile]);
+
+impl Sources<'_> {
+    fn prefix(&self, file: &ConfigFile) -> String {
+        match self.0.len() {
+            0 | 1 => String::new(),
+            _ => format!("{}: ", file.path.display()),
+        }
+    }
+
+    fn setting(&self, key: &str) -> String {
+        match self.0.iter().find(|file| file.raw.get(key).is_some()) {
+            Some(file) => format!("{}{}", self.prefix(file), key),
+            None => key.to_string(),
+        }
+    }
+
+    fn host(&self, h: usize) -> String {
+        match self.0.iter().find(|file| file.hosts.contains(&h)) {
+            Some(file) => format!("{}hosts[{}]", self.prefix(file), h - file.hosts.start),
+            None => format!("hosts[{}]", h),
+        }
+    }
+}
+
+/// Find mistakes in a configuration that parses but cannot work as meant.
+/// `files` are those it was loaded from, to attribute problems to them.
+pub fn validate(config: &AppConfig, files: &[ConfigFile]) -> Vec<Issue> {
+    let mut issues = Issues(Vec::new());
+    let sources = Sources(files);
+
+    if config.monitoring_interval_seconds == 0 {
+        issues.push(
+            sources.setting("monitoring_interval_seconds"),
+            "must be at least 1",
+        );
+    }
+    if let Some(proxy) = &config.proxy {
+        validate_proxy(&sources.setting("proxy"), proxy.url.expose(), &mut issues);
+    }
+
+    let mut aliases: HashMap<String, String> = HashMap::new();
+    for (h, host) in config.hosts.iter().enumerate() {
+        let host_location = sources.host(h);
+        if host.address.trim().is_empty() {
+            issues.push(format!("{}.address", host_location), "must not be empty");
+        }
+        for (c, check) in host.checks.iter().enumerate() {
+            let location = format!("{}.checks[{}]", host_location, c);
+
+            // Badges and metrics look targets up by name
+            let alias = host.target_alias(check);
+            match aliases.get(&alias) {
+                Some(first) => issues.push(
+                    &location,
+                    format!("target name '{}' is already used by {}", alias, first),
+                ),
+                None => {
+                    aliases.insert(alias, location.clone());
+                }
+            }
+
+            validate_check(&location, &host.address, check, config, &mut issues);
+        }
+    }
+    issues.0
+}
+
+fn validate_check(
+    location: &str,
+    address: &str,
+    check: &Check,
+    config: &AppConfig,
+    issues: &mut Issues,
+) {
+    if check.port() == 0 {
+        issues.push(format!("{}.port", location), "must be between 1 and 65535");
+    }
+    if check.timeout_seconds() == 0 {
+        issues.push(
+            format!("{}.timeout_seconds", location),
+            "must be at least 1",
+        );
+    }
+    if let Some(tls) = check.tls() {
+        validate_tls(&format!("{}.tls", location), tls, issues);
+    }
+    // Checks inherit the global proxy, which is reported once
+    if let Some(proxy) = check
+        .proxy()
+        .filter(|proxy| Some(*proxy) != config.proxy.as_ref())
+    {
+        validate_proxy(&format!("{}.proxy", location), proxy.url.expose(), issues);
+    }
+
+    match check {
+        Check::Http(http) => validate_http(location, address, http, issues),
+        Check::RabbitMQ(rabbitmq) if rabbitmq.port > u16::MAX - 10000 => issues.push(
+            format!("{}.port", location),
+            format!(
+                "management API port {} + 10000 is out of range",
+                rabbitmq.port
+            ),
+        ),
+        Check::Elasticsearch(elasticsearch) => {
+            if let Some(auth) = &elasticsearch.auth {
+                validate_auth(&format!("{}.auth", location), auth, issues);
+            }
+        }
+        Check::WebSocket(websocket) => {
+            if let Some(auth) = &websocket.auth {
+                validate_auth(&format!("{}.auth", location), auth, issues);
+                if matches!(auth, AuthConfig::Digest { .. }) {
+                    issues.push(
+                        format!("{}.auth", location),
+                        "Digest authentication is not supported for WebSocket checks",
+                    );
+                }
+            }
+            validate_assertions(location, websocket.assertions.as_deref(), issues);
+        }
+        _ => {}
+    }
+}
+
+fn validate_http(location: &str, address: &str, http: &HttpCheck, issues: &mut Issues) {
+    if !(100..=599).contains(&http.expected_status_code) {
+        issues.push(
+            format!("{}.expected_status_code", location),
+            format!("{} is not an HTTP status code", http.expected_status_code),
+        );
+    }
+    if let Some(pattern) = &http.body_regex_check {
+        if let Err(e) = regex::Regex::new(pattern) {
+            issues.push(
+                format!("{}.body_regex_check", location),
+                format!("invalid regex: {}", e),
+            );
+        }
+    }
+    let bodies = [
+        http.body.is_some(),
+        http.body_file.is_some(),
+        http.json.is_some(),
+        http.form.is_some(),
+    ];
+    if bodies.iter().filter(|set| **set).count() > 1 {
+        issues.push(
+            location,
+            "only one of body, body_file, json or form may be set",
+        );
+    }
+    if let Some(path) = &http.body_file {
+        if !std::path::Path::new(path).is_file() {
+            issues.push(
+                format!("{}.body_file", location),
+                format!("file '{}' not found", path),
+            );
+        }
+    }
+    // As at run time, a host exempted by no_proxy is connected to directly
+    let proxied = matches!(
+        proxy::select(http.proxy.as_ref(), address, http.port),
+        Ok(Some(_))
+    );
+    if http.check_each_address && proxied {
+        issues.push(
+            location,
+            "check_each_address cannot be used through a proxy",
+        );
+    }
+    if let Some(auth) = &http.auth {
+        validate_auth(&format!("{}.auth", location), auth, issues);
+    }
+    validate_assertions(location, http.assertions.as_deref(), issues);
+}
+
+fn validate_assertions(location: &str, assertions: Option<&[HttpAssertion]>, issues: &mut Issues) {
+    for (i, assertion) in assertions.unwrap_or_default().iter().enumerate() {
+        let location = format!("{}.assertions[{}]", location, i);
+        match &assertion.query {
+            AssertionQuery::JsonPath { path } => {
+                if let Err(e) = jsonpath_lib::Compiled::compile(path) {
+                    issues.push(&location, format!("invalid JSONPath '{}': {}", path, e));
+                }
+            }
+            AssertionQuery::XPath { path } => match sxd_xpath::Factory::new().build(path) {
+                Ok(Some(_)) => {}
+                Ok(None) => issues.push(&location, "XPath expression is empty"),
+                Err(e) => issues.push(&location, format!("invalid XPath '{}': {}", path, e)),
+            },
+            AssertionQuery::Regex { pattern } => {
+                if let Err(e) = regex::Regex::new(pattern) {
+                    issues.push(&location, format!("invalid regex: {}", e));
+                }
+            }
+            _ => {}
+        }
+        if let (
+            AssertionPredicate::Matches | AssertionPredicate::NotMatches,
+            AssertionValue::String(pattern),
+        ) = (&assertion.predicate, &assertion.value)
+        {
+            if let Err(e) = regex::Regex::new(pattern) {
+                issues.push(&location, format!("invalid regex in value: {}", e));
+            }
+        }
+    }
+}
+
+fn validate_auth(location: &str, auth: &AuthConfig, issues: &mut Issues) {
+    match auth {
+        AuthConfig::OAuth2(oauth2)
+            if !oauth2.token_url.starts_with("http://")
+                && !oauth2.token_url.starts_with("https://") =>
+        {
+            issues.push(
+                format!("{}.OAuth2.token_url", location),
+                "must start with http:// or https://",
+            )
+        }
+        AuthConfig::ApiKey { name, .. } if name.trim().is_empty() => {
+            issues.push(format!("{}.ApiKey.name", location), "must not be empty")
+        }
+        _ => {}
+    }
+}
+
+fn validate_tls(location: &str, tls: &TlsClientConfig, issues: &mut Issues) {
+    let files = [
+        ("ca_file", &tls.ca_file),
+        ("client_cert", &tls.client_cert),
+        ("client_key", &tls.client_key),
+    ];
+    for (field, path) in files {
+        if let Some(path) = path
+            .as_deref()
+            .filter(|p| !std::path::Path::new(p).is_file())
+        {
+            issues.push(
+                format!("{}.{}", location, field),
+                format!("file '{}' not found", path),
+            );
+        }
+    }
+}
+
+fn validate_proxy(location: &str, url: &str, issues: &mut Issues) {
+    if let Err(e) = Proxy::parse(url) {
+        issues.push(format!("{}.url", location), e);
+    }
+}
+
+/// Settings in the files that the configuration does not read, typically
+/// misspelt: every key of a file missing from `config` serialized back.
+pub fn unknown_settings(files: &[ConfigFile], config: &AppConfig) -> Vec<Issue> {
+    let mut issues = Issues(Vec::new());
+    let Ok(toml::Value::Table(known)) = toml::Value::try_from(config) else {
+        return issues.0;
+    };
+    let known_hosts = known.get("hosts").and_then(toml::Value::as_array);
+    let sources = Sources(files);
+    for file in files {
+        // `include` is read while loading, and a file has only its own hosts
+        let mut file_known = known.clone();
+        if let Some(include) = file.raw.get("include") {
+            file_known.insert("include".to_string(), include.clone());
+        }
+        let hosts = known_hosts.and_then(|hosts| hosts.get(file.hosts.clone()));
+        file_known.insert(
+            "hosts".to_string(),
+            toml::Value::Array(hosts.unwrap_or_default().to_vec()),
+        );
+        let mut file_issues = Issues(Vec::new());
+        compare_keys(
+            &file.raw,
+            &toml::Value::Table(file_known),
+            "",
+            &mut file_issues,
+        );
+        let prefix = sources.prefix(file);
+        issues
+            .0
+            .extend(file_issues.0.into_iter().map(|issue| Issue {
+                location: format!("{}{}", prefix, issue.location),
+                ..issue
+            }));
+    }
+    issues.0
+}
+
+fn compare_keys(raw: &toml::Value, known: &toml::Value, location: &str, issues: &mut Issues) {
+    match (raw, known) {
+        (toml::Value::Table(raw), toml::Value::Table(known)) => {
+            for (key, value) in raw {
+                let location = match location {
+                    "" => key.clone(),
+                    _ => format!("{}.{}", location, key),
+                };
+                match known.get(key) {
+                    Some(known) => compare_keys(value, known, &location, issues),
+                    None => issues.push(location, "unknown setting"),
+                }
+            }
+        }
+        (toml::Value::Array(raw), toml::Value::Array(known)) => {
+            for (i, (value, known)) in raw.iter().zip(known).enumerate() {
+                compare_keys(value, known, &format!("{}[{}]", location, i), issues);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn issues_for(toml_content: &str) -> Vec<String> {
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        writeln!(tmp_file, "{}", toml_content).unwrap();
+        match load_and_validate(tmp_file.path().to_str().unwrap()) {
+            Ok(_) => Vec::new(),
+            Err(issues) => issues.iter().map(|issue| issue.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_valid_config_has_no_issues() {
+        let issues = issues_for(
+            r#"
+[[hosts]]
+address = "example.com"
+  [[hosts.checks]]
+  type = "Http"
+  port = 443
+  path = "/"
+  protocol = "Https"
+  method = "Get"
+  body_regex_check = "status.*ok"
+  auth = { Basic = { username = "monitor", password = "secret" } }
+  [[hosts.checks.assertions]]
+  query = { JsonPath = { path = "$.status" } }
+  predicate = "Matches"
+  value = { String = "^(ok|degraded)$" }
+
+  [[hosts.checks]]
+  type = "Tcp"
+  port = 22
+"#,
+        );
+        assert!(issues.is_empty(), "{:?}", issues);
+    }
+
+    #[test]
+    fn test_reports_every_problem_with_its_location() {
+        let issues = issues_for(
+            r#"
+monitoring_interval_seconds = 0
+
+[[hosts]]
+address = "example.com"
+  [[hosts.checks]]
+  type = "Http"
+  name = "API"
+  port = 0
+  path = "/"
+  protocol = "Https"
+  method = "Get"
+  body_regex_check = "status(ok"
+  auth = { Basic = { username = "monitor", passwrd = "secret", password = "secret" } }
+  [[hosts.checks.assertions]]
+  query = { JsonPath = { path = "$.[" } }
+  predicate = "Exists"
+  value = "Null"
+
+[[hosts]]
+address = "example.org"
+  [[hosts.checks]]
+  type = "Tcp"
+  name = "API"
+  port = 22
+  timeout = 5
+"#,
+        );
+        let expected_prefixes = [
+            "hosts[0].checks[0].auth.Basic.passwrd: unknown setting",
+            "hosts[1].checks[0].timeout: unknown setting",
+            "monitoring_interval_seconds: must be at least 1",
+            "hosts[0].checks[0].port: must be between 1 and 65535",
+            "hosts[0].checks[0].body_regex_check: invalid regex",
+            "hosts[0].checks[0].assertions[0]: invalid JSONPath '$.['",
+            "hosts[1].checks[0]: target name 'API' is already used by hosts[0].checks[0]",
+        ];
+        assert_eq!(issues.len(), expected_prefixes.len(), "{:#?}", issues);
+        for prefix in expected_prefixes {
+            assert!(
+                issues.iter().any(|issue| issue.starts_with(prefix)),
+                "missing '{}' in {:#?}",
+                prefix,
+                issues
+            );
+        }
+    }
+
+    #[test]
+    fn test_issues_in_a_split_config_name_their_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = |name: &str, contents: &str| {
+            std::fs::write(dir.path().join(name), contents).unwrap();
+            dir.path().join(name).display().to_string()
+        };
+        let global = file("00-global.toml", "monitoring_interval_seconds = 0\n");
+        file(
+            "10-db.toml",
+            "[[hosts]]\naddress = \"db.internal\"\n  [[hosts.checks]]\n  type = \"Tcp\"\n  name = \"DB\"\n  port = 5432\n",
+        );
+        let web = file(
+            "20-web.toml",
+            "[[hosts]]\naddress = \"web.internal\"\nchecks = []\n\n[[hosts]]\naddress = \"db.replica\"\n  [[hosts.checks]]\n  type = \"Tcp\"\n  name = \"DB\"\n  port = 5432\n  timeout = 5\n",
+        );
+
+        let issues: Vec<String> = load_and_validate(dir.path().to_str().unwrap())
+            .unwrap_err()
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                format!("{}: hosts[1].checks[0].timeout: unknown setting", web),
+                format!("{}: monitoring_interval_seconds: must be at least 1", global),
+                format!(
+                    "{}: hosts[1].checks[0]: target name 'DB' is already used by {}: hosts[0].checks[0]",
+                    web,
+                    dir.path().join("10-db.toml").display()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_each_address_allowed_when_no_proxy_exempts_the_host() {
+        let config = |address: &str| {
+            format!(
+                r#"
+proxy = {{ url = "http://proxy.example.com:3128", no_proxy = ["internal.example.com"] }}
+
+[[hosts]]
+address = "{}"
+  [[hosts.checks]]
+  type = "Http"
+  port = 443
+  path = "/"
+  protocol = "Https"
+  method = "Get"
+  check_each_address = true
+"#,
+                address
+            )
+        };
+        let issues = issues_for(&config("api.internal.example.com"));
+        assert!(issues.is_empty(), "{:?}", issues);
+
+        assert_eq!(
+            issues_for(&config("api.example.com")),
+            ["hosts[0].checks[0]: check_each_address cannot be used through a proxy"]
+        );
+    }
+
+    #[test]
+    fn test_unloadable_file_is_one_issue() {
+        let issues = issues_for("[[hosts]]\nchecks = []\n");
+        assert_eq!(issues.len(), 1);
+        assert!(
+            issues[0].contains("missing field `address`"),
+            "{:?}",
+            issues
+        );
+    }
+}
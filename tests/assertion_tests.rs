@@ -1,4 +1,3 @@
-use reqwest;
 use uptime_monitor::config::*;
 use uptime_monitor::test_utils::*;
 
@@ -260,19 +259,16 @@ fn test_complex_assertion_combinations() {
 
     // Manually test each assertion
     for assertion in assertions {
-        match &assertion.query {
-            AssertionQuery::JsonPath { path } => {
-                let results = jsonpath_lib::select(&json, &path).unwrap();
-                if !results.is_empty() {
-                    let actual = results[0];
-                    assert!(evaluate_predicate(
-                        &assertion.predicate,
-                        actual,
-                        &assertion.value
-                    ));
-                }
+        if let AssertionQuery::JsonPath { path } = &assertion.query {
+            let results = jsonpath_lib::select(&json, path).unwrap();
+            if !results.is_empty() {
+                let actual = results[0];
+                assert!(evaluate_predicate(
+                    &assertion.predicate,
+                    actual,
+                    &assertion.value
+                ));
             }
-            _ => {}
         }
     }
 }
@@ -290,19 +286,267 @@ fn test_negative_assertions() {
     // Test NotEquals predicate
     let results = jsonpath_lib::select(&json, "$.args.test_param").unwrap();
     assert!(!values_equal(
-        &results[0],
+        results[0],
         &AssertionValue::String("goodbye".to_string())
     ));
 
     // Test NotContains predicate
     let url_results = jsonpath_lib::select(&json, "$.url").unwrap();
     assert!(!string_predicate(
-        &url_results[0],
+        url_results[0],
         &AssertionValue::String("forbidden".to_string()),
         |a, b| a.contains(b)
     ));
 }
 
+#[test]
+fn test_certificate_assertions() {
+    let cert = TlsCertInfo {
+        subject: Some("CN=example.com".to_string()),
+        issuer: Some("C=US, O=Let's Encrypt, CN=R3".to_string()),
+        serial: Some("03A1F2".to_string()),
+        not_before: Some("2024-01-01T00:00:00Z".to_string()),
+        not_after: Some(
+            (chrono::Utc::now() + chrono::Duration::days(45))
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        ),
+        signature_algorithm: Some("sha256WithRSAEncryption".to_string()),
+        ..Default::default()
+    };
+    let assertion = |field, predicate, value: &str| {
+        create_test_assertion(
+            AssertionQuery::Certificate { field },
+            predicate,
+            AssertionValue::String(value.to_string()),
+        )
+    };
+    let assertions = vec![
+        assertion(
+            CertificateField::Issuer,
+            AssertionPredicate::Contains,
+            "Let's Encrypt",
+        ),
+        assertion(
+            CertificateField::NotAfter,
+            AssertionPredicate::GreaterThan,
+            "now+20d",
+        ),
+        assertion(
+            CertificateField::NotBefore,
+            AssertionPredicate::LessThan,
+            "now",
+        ),
+        assertion(
+            CertificateField::Algorithm,
+            AssertionPredicate::StartsWith,
+            "sha256",
+        ),
+        assertion(
+            CertificateField::NotAfter,
+            AssertionPredicate::GreaterThan,
+            "now+60d",
+        ),
+    ];
+
+    let results = evaluate_assertions_with_data(
+        &assertions,
+        http::StatusCode::OK,
+        &http::HeaderMap::new(),
+        "",
+        10,
+        Some(&cert),
+        &[],
+    );
+    let passed: Vec<bool> = results.iter().map(|r| r.passed).collect();
+    assert_eq!(passed, vec![true, true, true, true, false]);
+
+    // Without a certificate (plain HTTP) the query yields no value
+    let results = evaluate_assertions_with_data(
+        &assertions[..1],
+        http::StatusCode::OK,
+        &http::HeaderMap::new(),
+        "",
+        10,
+        None,
+        &[],
+    );
+    assert!(!results[0].passed);
+    assert!(results[0].message.contains("returned no value"));
+}
+
+fn xpath_assertion(
+    path: &str,
+    predicate: AssertionPredicate,
+    value: AssertionValue,
+) -> HttpAssertion {
+    create_test_assertion(
+        AssertionQuery::XPath {
+            path: path.to_string(),
+        },
+        predicate,
+        value,
+    )
+}
+
+fn evaluate_body(assertions: &[HttpAssertion], content_type: &str, body: &str) -> Vec<bool> {
+    let mut headers = http::HeaderMap::new();
+    headers.insert(
+        http::header::CONTENT_TYPE,
+        http::HeaderValue::from_str(content_type).unwrap(),
+    );
+    evaluate_assertions_with_data(
+        assertions,
+        http::StatusCode::OK,
+        &headers,
+        body,
+        10,
+        None,
+        &[],
+    )
+    .iter()
+    .map(|r| r.passed)
+    .collect()
+}
+
+#[test]
+fn test_xpath_assertions_soap_response() {
+    let soap = r#"<?xml version="1.0" encoding="UTF-8"?>
+<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/"
+               xmlns:m="https://www.example.org/stock">
+  <soap:Header/>
+  <soap:Body>
+    <m:GetStockPriceResponse>
+      <m:Symbol>ACME</m:Symbol>
+      <m:Price currency="USD">34.5</m:Price>
+      <m:Open>true</m:Open>
+    </m:GetStockPriceResponse>
+  </soap:Body>
+</soap:Envelope>"#;
+
+    let assertions = vec![
+        xpath_assertion(
+            "//m:GetStockPriceResponse/m:Symbol",
+            AssertionPredicate::Equals,
+            AssertionValue::String("ACME".to_string()),
+        ),
+        xpath_assertion(
+            "number(//m:Price)",
+            AssertionPredicate::GreaterThan,
+            AssertionValue::Number(30.0),
+        ),
+        xpath_assertion(
+            "string(//m:Price/@currency)",
+            AssertionPredicate::Equals,
+            AssertionValue::String("USD".to_string()),
+        ),
+        xpath_assertion(
+            "boolean(//soap:Fault)",
+            AssertionPredicate::Equals,
+            AssertionValue::Boolean(false),
+        ),
+        xpath_assertion(
+            "//soap:Fault/faultstring",
+            AssertionPredicate::NotExists,
+            AssertionValue::Null,
+        ),
+    ];
+
+    assert_eq!(
+        evaluate_body(&assertions, "text/xml; charset=utf-8", soap),
+        vec![true; 5]
+    );
+}
+
+#[test]
+fn test_xpath_assertions_rss_feed() {
+    let rss = r#"<?xml version="1.0"?>
+<rss version="2.0">
+  <channel>
+    <title>Status Updates</title>
+    <item><title>All systems operational</title><pubDate>Mon, 06 Jan 2025 10:00:00 GMT</pubDate></item>
+    <item><title>Scheduled maintenance</title></item>
+    <item><title>Database failover completed</title></item>
+  </channel>
+</rss>"#;
+
+    let assertions = vec![
+        xpath_assertion(
+            "count(/rss/channel/item)",
+            AssertionPredicate::Equals,
+            AssertionValue::Integer(3),
+        ),
+        xpath_assertion(
+            "/rss/channel/item[1]/title",
+            AssertionPredicate::StartsWith,
+            AssertionValue::String("All systems".to_string()),
+        ),
+        xpath_assertion(
+            "/rss/channel/item/title",
+            AssertionPredicate::IsCollection,
+            AssertionValue::Null,
+        ),
+        xpath_assertion(
+            "/rss/@version",
+            AssertionPredicate::Equals,
+            AssertionValue::String("2.0".to_string()),
+        ),
+        xpath_assertion(
+            "count(/rss/channel/item)",
+            AssertionPredicate::GreaterThan,
+            AssertionValue::Integer(5),
+        ),
+    ];
+
+    assert_eq!(
+        evaluate_body(&assertions, "application/rss+xml", rss),
+        vec![true, true, true, true, false]
+    );
+}
+
+#[test]
+fn test_xpath_assertions_lenient_html() {
+    let html = r#"<!DOCTYPE html>
+<html lang=en>
+<head><title>Service Status</title><meta charset="utf-8"></head>
+<body>
+  <div id=status class="badge ok">Operational &nbsp;</div>
+  <table>
+    <tr><td>api<td>up
+    <tr><td>db<td>up
+  </table>
+  <p>Last check: 2 minutes ago<br>
+</body>
+</html>"#;
+
+    let assertions = vec![
+        xpath_assertion(
+            "/html/head/title",
+            AssertionPredicate::Equals,
+            AssertionValue::String("Service Status".to_string()),
+        ),
+        xpath_assertion(
+            "//div[@id='status']",
+            AssertionPredicate::Contains,
+            AssertionValue::String("Operational".to_string()),
+        ),
+        xpath_assertion(
+            "count(//tr)",
+            AssertionPredicate::Equals,
+            AssertionValue::Integer(2),
+        ),
+        xpath_assertion(
+            "normalize-space(//tr[td[1]='db']/td[2])",
+            AssertionPredicate::Equals,
+            AssertionValue::String("up".to_string()),
+        ),
+    ];
+
+    assert_eq!(
+        evaluate_body(&assertions, "text/html; charset=utf-8", html),
+        vec![true; 4]
+    );
+}
+
 #[test]
 fn test_assertion_formatting() {
     // Test query formatting
@@ -394,7 +638,9 @@ async fn test_httpbin_auth_integration() {
 }
 
 // Import the functions we're testing
+use uptime_monitor::monitoring::tls::TlsCertInfo;
 use uptime_monitor::monitoring::{
-    compare_values, evaluate_predicate, format_assertion_value, format_predicate, format_query,
-    is_ipv4, is_ipv6, is_iso_date, is_uuid, regex_predicate, string_predicate, values_equal,
+    compare_values, evaluate_assertions_with_data, evaluate_predicate, format_assertion_value,
+    format_predicate, format_query, is_ipv4, is_ipv6, is_iso_date, is_uuid, regex_predicate,
+    string_predicate, values_equal,
 };
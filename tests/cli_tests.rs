@@ -0,0 +1,307 @@
+use std::io::Write;
+use std::process::{Command, Output};
+use tempfile::NamedTempFile;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn config_file(contents: &str) -> NamedTempFile {
+    let mut file = NamedTempFile::new().unwrap();
+    writeln!(file, "{}", contents).unwrap();
+    file
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_uptime_monitor"))
+        .args(args)
+        .env_remove("TEST_CONFIG_PATH")
+        .env_remove("UPTIME_MONITOR_CONFIG")
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+const CONFIG: &str = r#"
+[[hosts]]
+address = "db.internal"
+alias = "Database"
+  [[hosts.checks]]
+  type = "Tcp"
+  port = 5432
+
+[[hosts]]
+address = "api.example.com"
+  [[hosts.checks]]
+  type = "Http"
+  name = "API"
+  port = 443
+  path = "/health"
+  protocol = "Https"
+  method = "Get"
+  auth = { Basic = { username = "monitor", password = "hunter2-secret" } }
+"#;
+
+#[test]
+fn validate_reports_problems_and_exit_status() {
+    let valid = config_file(CONFIG);
+    let output = run(&["validate", valid.path().to_str().unwrap()]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("is valid: 2 hosts, 2 checks"));
+
+    let invalid = config_file(&CONFIG.replace("port = 5432", "port = 0\n  timeuot = 5"));
+    let output = run(&["validate", "--config", invalid.path().to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+    let errors = stderr(&output);
+    assert!(
+        errors.contains("hosts[0].checks[0].timeuot: unknown setting"),
+        "{}",
+        errors
+    );
+    assert!(
+        errors.contains("hosts[0].checks[0].port: must be between 1 and 65535"),
+        "{}",
+        errors
+    );
+    assert!(errors.contains("2 problem(s) found"), "{}", errors);
+}
+
+#[test]
+fn list_targets_prints_names_types_and_urls() {
+    let config = config_file(CONFIG);
+    let output = run(&["list-targets", "-c", config.path().to_str().unwrap()]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let lines: Vec<String> = stdout(&output)
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect();
+    assert_eq!(
+        lines,
+        [
+            "NAME TYPE URL",
+            "Database (TCP:5432) TCP tcp://db.internal:5432",
+            "API HTTP https://api.example.com:443/health",
+        ]
+    );
+}
+
+#[test]
+fn export_redacts_secrets() {
+    let config = config_file(CONFIG);
+    let output = run(&["export", "--format=json", config.path().to_str().unwrap()]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let exported: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(
+        exported["hosts"][1]["checks"][0]["auth"]["Basic"]["password"],
+        "[REDACTED]"
+    );
+    assert!(!stdout(&output).contains("hunter2-secret"));
+
+    let output = run(&["export", config.path().to_str().unwrap()]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("address = \"db.internal\""));
+}
+
+#[tokio::test]
+async fn check_runs_every_check_once() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    let config = config_file(&format!(
+        r#"
+[[hosts]]
+address = "{}"
+  [[hosts.checks]]
+  type = "Http"
+  name = "Up"
+  port = {}
+  path = "/health"
+  protocol = "Http"
+  method = "Get"
+
+  [[hosts.checks]]
+  type = "Http"
+  name = "Missing"
+  port = {}
+  path = "/missing"
+  protocol = "Http"
+  method = "Get"
+"#,
+        server.address().ip(),
+        server.address().port(),
+        server.address().port()
+    ));
+    let config_path = config.path().to_str().unwrap().to_string();
+
+    let output = tokio::task::spawn_blocking(move || run(&["check", "--config", &config_path]))
+        .await
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1), "{}", stderr(&output));
+    let lines: Vec<String> = stdout(&output).lines().map(str::to_string).collect();
+    assert_eq!(lines.len(), 5, "{:?}", lines);
+    assert!(lines[0].starts_with("STATUS "), "{}", lines[0]);
+    assert!(
+        lines[1].starts_with("HEALTHY    Up       HTTP"),
+        "{}",
+        lines[1]
+    );
+    assert!(
+        lines[2].starts_with("UNHEALTHY  Missing  HTTP"),
+        "{}",
+        lines[2]
+    );
+    assert!(lines[2].contains("404"), "{}", lines[2]);
+    assert_eq!(lines[4], "2 checks: 1 healthy, 1 unhealthy");
+}
+
+// A production host whose status assertion fails, and a staging host
+async fn tagged_hosts(server: &MockServer) -> NamedTempFile {
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"status":"degraded"}"#))
+        .mount(server)
+        .await;
+    config_file(&format!(
+        r#"
+[[hosts]]
+address = "{}"
+tags = ["production"]
+  [[hosts.checks]]
+  type = "Http"
+  name = "Up"
+  port = {}
+  path = "/health"
+  protocol = "Http"
+  method = "Get"
+  [[hosts.checks.assertions]]
+  query = {{ JsonPath = {{ path = "$.status" }} }}
+  predicate = "Equals"
+  value = {{ String = "ok" }}
+
+[[hosts]]
+address = "{}"
+tags = ["staging"]
+  [[hosts.checks]]
+  type = "Tcp"
+  name = "Closed"
+  port = 1
+"#,
+        server.address().ip(),
+        server.address().port(),
+        server.address().ip(),
+    ))
+}
+
+#[tokio::test]
+async fn check_filters_targets_and_reports_json() {
+    let server = MockServer::start().await;
+    let config = tagged_hosts(&server).await;
+    let config_path = config.path().to_str().unwrap().to_string();
+
+    let output = tokio::task::spawn_blocking(move || {
+        let production = run(&[
+            "check",
+            "--tag",
+            "production",
+            "--format",
+            "json",
+            &config_path,
+        ]);
+        let nothing = run(&["check", "--name", "Nope", &config_path]);
+        (production, nothing)
+    })
+    .await
+    .unwrap();
+    let (production, nothing) = output;
+
+    assert_eq!(production.status.code(), Some(1), "{}", stderr(&production));
+    let report: serde_json::Value = serde_json::from_str(&stdout(&production)).unwrap();
+    assert_eq!(report["healthy"], false);
+    assert_eq!(report["total"], 1);
+    let result = &report["results"][0];
+    assert_eq!(result["name"], "Up");
+    let assertion = &result["result"]["Http"]["assertions"][0];
+    assert_eq!(assertion["passed"], false);
+    assert_eq!(assertion["actual"], "degraded");
+
+    assert_eq!(nothing.status.code(), Some(2));
+    assert!(stderr(&nothing).contains("No target matches the filter"));
+}
+
+#[tokio::test]
+async fn check_reports_junit_and_tap() {
+    let server = MockServer::start().await;
+    let config = tagged_hosts(&server).await;
+    let config_path = config.path().to_str().unwrap().to_string();
+
+    let (junit, tap) = tokio::task::spawn_blocking(move || {
+        (
+            run(&["check", "--format", "junit", &config_path]),
+            run(&["check", "--format=tap", "--host", "127.0.0.1", &config_path]),
+        )
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(junit.status.code(), Some(1), "{}", stderr(&junit));
+    let xml = stdout(&junit);
+    assert!(xml.contains("tests=\"3\" failures=\"3\""), "{}", xml);
+    assert!(
+        xml.contains("<testcase name=\"Up\" classname=\"HTTP.127.0.0.1\""),
+        "{}",
+        xml
+    );
+    assert!(
+        xml.contains(
+            "<testcase name=\"jsonpath[$.status] == &quot;ok&quot;\" classname=\"HTTP.127.0.0.1\""
+        ),
+        "{}",
+        xml
+    );
+    assert!(
+        xml.contains(
+            "type=\"AssertionFailed\">expected: &quot;ok&quot;\nactual: degraded</failure>"
+        ),
+        "{}",
+        xml
+    );
+    assert!(
+        xml.contains("<testcase name=\"Closed\" classname=\"TCP.127.0.0.1\""),
+        "{}",
+        xml
+    );
+
+    let tap = stdout(&tap);
+    assert!(
+        tap.starts_with("TAP version 14\n1..2\n# Subtest: Up\n"),
+        "{}",
+        tap
+    );
+    assert!(
+        tap.contains("    not ok 1 - jsonpath[$.status] == \"ok\"\n"),
+        "{}",
+        tap
+    );
+    assert!(tap.contains("\nnot ok 1 - Up\n"), "{}", tap);
+    assert!(tap.contains("\nnot ok 2 - Closed\n"), "{}", tap);
+}
+
+#[test]
+fn usage_errors_exit_with_status_2() {
+    let output = run(&["frobnicate"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("Unknown command: frobnicate"));
+    assert!(stderr(&output).contains("Usage: uptime_monitor"));
+
+    let output = run(&["--help"]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("list-targets"));
+}
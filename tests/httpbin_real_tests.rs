@@ -1,13 +1,12 @@
-use reqwest;
-use serde_json::{json, Value};
+use serde_json::Value;
 use std::collections::HashMap;
 use uptime_monitor::config::*;
 use uptime_monitor::monitoring::evaluate_predicate;
-use uptime_monitor::test_utils::*;
 
 /// Test helper to create HTTPS httpbin check
 fn create_httpbin_https_check(path: &str, method: HttpMethod, expected_status: u16) -> HttpCheck {
     HttpCheck {
+        name: None,
         port: 443,
         path: path.to_string(),
         protocol: HttpProtocol::Https,
@@ -23,6 +22,18 @@ fn create_httpbin_https_check(path: &str, method: HttpMethod, expected_status: u
             predicate: AssertionPredicate::Equals,
             value: AssertionValue::Integer(expected_status as i64),
         }]),
+        max_body_bytes: 10 * 1024 * 1024,
+        follow_redirects: false,
+        max_redirects: 10,
+        redirect_policy: RedirectPolicy::AnyHost,
+        body: None,
+        body_file: None,
+        json: None,
+        form: None,
+        address_family: AddressFamily::Both,
+        check_each_address: false,
+        tls: None,
+        proxy: None,
     }
 }
 
@@ -33,7 +44,7 @@ async fn make_httpbin_request(
     let client = reqwest::Client::new();
     let url = format!("https://httpbin.org{}", check.path);
 
-    let mut request_builder = match check.method {
+    let mut request_builder = match &check.method {
         HttpMethod::Get => client.get(&url),
         HttpMethod::Post => client.post(&url),
         HttpMethod::Put => client.put(&url),
@@ -43,6 +54,10 @@ async fn make_httpbin_request(
             // Options might not be directly supported, use GET as fallback
             client.get(&url)
         }
+        HttpMethod::Patch => client.patch(&url),
+        HttpMethod::Custom(name) => {
+            client.request(reqwest::Method::from_bytes(name.as_bytes()).unwrap(), &url)
+        }
     };
 
     // Add custom headers if specified
@@ -56,13 +71,30 @@ async fn make_httpbin_request(
     if let Some(ref auth) = check.auth {
         match auth {
             AuthConfig::Basic { username, password } => {
-                request_builder = request_builder.basic_auth(username, Some(password));
+                request_builder = request_builder.basic_auth(username, Some(password.expose()));
             }
             AuthConfig::Bearer { token } => {
-                request_builder = request_builder.bearer_auth(token);
+                request_builder = request_builder.bearer_auth(token.expose());
+            }
+            AuthConfig::ApiKey {
+                name,
+                value,
+                location: ApiKeyLocation::Header,
+            } => {
+                request_builder = request_builder.header(name, value.expose());
+            }
+            AuthConfig::ApiKey {
+                name,
+                value,
+                location: ApiKeyLocation::Query,
+            } => {
+                request_builder = request_builder.query(&[(name, value.expose())]);
             }
-            AuthConfig::OAuth2 { .. } => {
-                // OAuth2 is more complex, would need token exchange
+            AuthConfig::OAuth2(_)
+            | AuthConfig::Digest { .. }
+            | AuthConfig::Hmac(_)
+            | AuthConfig::AwsSigV4(_) => {
+                // These need a token exchange, a challenge or per-request signing
                 // For testing, we'll skip this
             }
         }
@@ -182,7 +214,7 @@ async fn test_httpbin_basic_auth_real() {
         create_httpbin_https_check("/basic-auth/testuser/testpass", HttpMethod::Get, 200);
     check.auth = Some(AuthConfig::Basic {
         username: "testuser".to_string(),
-        password: "testpass".to_string(),
+        password: "testpass".into(),
     });
 
     let result = make_httpbin_request(&check).await;
@@ -212,7 +244,7 @@ async fn test_httpbin_basic_auth_real() {
 async fn test_httpbin_bearer_auth_real() {
     let mut check = create_httpbin_https_check("/bearer", HttpMethod::Get, 200);
     check.auth = Some(AuthConfig::Bearer {
-        token: "test-token-12345".to_string(),
+        token: "test-token-12345".into(),
     });
 
     let result = make_httpbin_request(&check).await;
@@ -614,10 +646,12 @@ async fn test_httpbin_monitoring_configuration_real() {
         monitoring_interval_seconds: 30,
         memory_cleanup_interval_minutes: 60,
         keep_history_hours: 24,
+        proxy: None,
         hosts: vec![
             HostConfig {
                 address: "httpbin.org".to_string(),
                 alias: Some("HttpBin-GET".to_string()),
+                tags: Vec::new(),
                 checks: vec![Check::Http(create_httpbin_https_check(
                     "/get",
                     HttpMethod::Get,
@@ -627,6 +661,7 @@ async fn test_httpbin_monitoring_configuration_real() {
             HostConfig {
                 address: "httpbin.org".to_string(),
                 alias: Some("HttpBin-Status".to_string()),
+                tags: Vec::new(),
                 checks: vec![Check::Http(create_httpbin_https_check(
                     "/status/200",
                     HttpMethod::Get,
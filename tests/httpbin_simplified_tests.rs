@@ -7,6 +7,7 @@ use uptime_monitor::test_utils::*;
 /// Test helper to create HTTPS httpbin check
 fn create_httpbin_https_check(path: &str, method: HttpMethod, expected_status: u16) -> HttpCheck {
     HttpCheck {
+        name: None,
         port: 443,
         path: path.to_string(),
         protocol: HttpProtocol::Https,
@@ -22,12 +23,26 @@ fn create_httpbin_https_check(path: &str, method: HttpMethod, expected_status: u
             predicate: AssertionPredicate::Equals,
             value: AssertionValue::Integer(expected_status as i64),
         }]),
+        max_body_bytes: 10 * 1024 * 1024,
+        follow_redirects: false,
+        max_redirects: 10,
+        redirect_policy: RedirectPolicy::AnyHost,
+        body: None,
+        body_file: None,
+        json: None,
+        form: None,
+        address_family: AddressFamily::Both,
+        check_each_address: false,
+        tls: None,
+        proxy: None,
     }
 }
 
-/// Test helper to create HTTP httpbin check  
+/// Test helper to create HTTP httpbin check
+#[allow(dead_code)]
 fn create_httpbin_http_check(path: &str, method: HttpMethod, expected_status: u16) -> HttpCheck {
     HttpCheck {
+        name: None,
         port: 80,
         path: path.to_string(),
         protocol: HttpProtocol::Http,
@@ -43,6 +58,18 @@ fn create_httpbin_http_check(path: &str, method: HttpMethod, expected_status: u1
             predicate: AssertionPredicate::Equals,
             value: AssertionValue::Integer(expected_status as i64),
         }]),
+        max_body_bytes: 10 * 1024 * 1024,
+        follow_redirects: false,
+        max_redirects: 10,
+        redirect_policy: RedirectPolicy::AnyHost,
+        body: None,
+        body_file: None,
+        json: None,
+        form: None,
+        address_family: AddressFamily::Both,
+        check_each_address: false,
+        tls: None,
+        proxy: None,
     }
 }
 
@@ -255,7 +282,7 @@ fn test_httpbin_basic_auth_check() {
         create_httpbin_https_check("/basic-auth/testuser/testpass", HttpMethod::Get, 200);
     check.auth = Some(AuthConfig::Basic {
         username: "testuser".to_string(),
-        password: "testpass".to_string(),
+        password: "testpass".into(),
     });
 
     assert_eq!(check.path, "/basic-auth/testuser/testpass");
@@ -263,7 +290,7 @@ fn test_httpbin_basic_auth_check() {
 
     if let Some(AuthConfig::Basic { username, password }) = &check.auth {
         assert_eq!(username, "testuser");
-        assert_eq!(password, "testpass");
+        assert_eq!(password.expose(), "testpass");
     } else {
         panic!("Expected Basic auth configuration");
     }
@@ -273,14 +300,14 @@ fn test_httpbin_basic_auth_check() {
 fn test_httpbin_bearer_auth_check() {
     let mut check = create_httpbin_https_check("/bearer", HttpMethod::Get, 200);
     check.auth = Some(AuthConfig::Bearer {
-        token: "test-bearer-token-123".to_string(),
+        token: "test-bearer-token-123".into(),
     });
 
     assert_eq!(check.path, "/bearer");
     assert!(check.auth.is_some());
 
     if let Some(AuthConfig::Bearer { token }) = &check.auth {
-        assert_eq!(token, "test-bearer-token-123");
+        assert_eq!(token.expose(), "test-bearer-token-123");
     } else {
         panic!("Expected Bearer auth configuration");
     }
@@ -289,23 +316,28 @@ fn test_httpbin_bearer_auth_check() {
 #[test]
 fn test_httpbin_oauth2_auth_check() {
     let mut check = create_httpbin_https_check("/oauth", HttpMethod::Get, 200);
-    check.auth = Some(AuthConfig::OAuth2 {
+    check.auth = Some(AuthConfig::OAuth2(OAuth2Config {
         client_id: "test-client-id".to_string(),
-        client_secret: "test-client-secret".to_string(),
+        client_secret: "test-client-secret".into(),
         token_url: "https://httpbin.org/oauth/token".to_string(),
-    });
+        scopes: Vec::new(),
+        audience: None,
+        extra_params: Default::default(),
+        client_auth: OAuth2ClientAuth::Post,
+    }));
 
     assert_eq!(check.path, "/oauth");
     assert!(check.auth.is_some());
 
-    if let Some(AuthConfig::OAuth2 {
+    if let Some(AuthConfig::OAuth2(OAuth2Config {
         client_id,
         client_secret,
         token_url,
-    }) = &check.auth
+        ..
+    })) = &check.auth
     {
         assert_eq!(client_id, "test-client-id");
-        assert_eq!(client_secret, "test-client-secret");
+        assert_eq!(client_secret.expose(), "test-client-secret");
         assert_eq!(token_url, "https://httpbin.org/oauth/token");
     } else {
         panic!("Expected OAuth2 auth configuration");
@@ -796,10 +828,12 @@ fn test_httpbin_monitoring_configuration() {
         monitoring_interval_seconds: 30,
         memory_cleanup_interval_minutes: 60,
         keep_history_hours: 24,
+        proxy: None,
         hosts: vec![
             HostConfig {
                 address: "httpbin.org".to_string(),
                 alias: Some("HttpBin-Status".to_string()),
+                tags: Vec::new(),
                 checks: vec![Check::Http(create_httpbin_https_check(
                     "/status/200",
                     HttpMethod::Get,
@@ -809,6 +843,7 @@ fn test_httpbin_monitoring_configuration() {
             HostConfig {
                 address: "httpbin.org".to_string(),
                 alias: Some("HttpBin-JSON".to_string()),
+                tags: Vec::new(),
                 checks: vec![Check::Http(create_httpbin_https_check(
                     "/json",
                     HttpMethod::Get,
@@ -818,12 +853,13 @@ fn test_httpbin_monitoring_configuration() {
             HostConfig {
                 address: "httpbin.org".to_string(),
                 alias: Some("HttpBin-Auth".to_string()),
+                tags: Vec::new(),
                 checks: vec![Check::Http({
                     let mut check =
                         create_httpbin_https_check("/basic-auth/user/pass", HttpMethod::Get, 200);
                     check.auth = Some(AuthConfig::Basic {
                         username: "user".to_string(),
-                        password: "pass".to_string(),
+                        password: "pass".into(),
                     });
                     check
                 })],
@@ -870,7 +906,7 @@ fn test_httpbin_ssl_certificate_configuration() {
     check.check_ssl_certificate = true;
 
     assert_eq!(check.protocol, HttpProtocol::Https);
-    assert_eq!(check.check_ssl_certificate, true);
+    assert!(check.check_ssl_certificate);
     assert_eq!(check.port, 443);
 }
 